@@ -1,52 +1,9 @@
 use esp_idf_svc::nvs::{EspNvs, NvsDefault};
-use std::num::ParseIntError;
+use game::logic::Highscores;
 
-const MAX_HIGHSCORES: usize = 10;
 pub const NVS_NAMESPACE: &str = "highscores";
 const NVS_KEY: &str = "scores_v2";
 
-#[derive(Debug)]
-pub struct Highscores {
-    pub scores: Vec<u32>,
-}
-
-impl Highscores {
-    pub fn add_score(&mut self, new_score: u32) {
-        self.scores.push(new_score);
-        //to sort the highscores
-        self.scores.sort_by(|a, b| b.cmp(a));
-        self.scores.truncate(MAX_HIGHSCORES);
-    }
-
-    fn serialize(&self) -> String {
-        self.scores
-            .iter()
-            .map(u32::to_string)
-            .reduce(|accum, elem| accum + "," + &elem)
-            .unwrap_or_default()
-    }
-
-    fn deserialize(string: &str) -> Result<Self, ParseIntError> {
-        Ok(Self {
-            scores: string.split(",").map(|str| str.parse()).try_fold(
-                Vec::new(),
-                |mut accum, maybe_elem| {
-                    accum.push(maybe_elem?);
-                    Ok(accum)
-                },
-            )?,
-        })
-    }
-}
-
-impl Default for Highscores {
-    fn default() -> Self {
-        Highscores {
-            scores: Vec::with_capacity(MAX_HIGHSCORES),
-        }
-    }
-}
-
 pub fn save_highscores(
     nvs: &mut EspNvs<NvsDefault>,
     highscores: &Highscores,
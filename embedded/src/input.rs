@@ -1,49 +1,131 @@
 use esp_idf_hal::gpio;
 use esp_idf_hal::gpio::{Input, InputPin, OutputPin, Pin, PinDriver, Pull};
 use esp_idf_hal::peripheral::Peripheral;
-use esp_idf_sys::EspError;
-use std::sync::atomic::{AtomicBool, Ordering};
+use esp_idf_hal::task::notification::Notifier;
+use esp_idf_sys::{EspError, esp_timer_get_time, gpio_get_level};
+use game::input::{Button, EventQueue, InputEvent, InputSource};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-pub static BUTTON_LEFT: AtomicBool = AtomicBool::new(false);
-pub static BUTTON_RIGHT: AtomicBool = AtomicBool::new(false);
-pub static BUTTON_DOWN: AtomicBool = AtomicBool::new(false);
-pub static BUTTON_ROTATE: AtomicBool = AtomicBool::new(false);
+/// Button edges as recorded by the interrupt handlers
+#[derive(Clone, Copy)]
+struct RawEvent {
+    button: Button,
+    pressed: bool,
+    /// Time since boot
+    micros: i64,
+}
+
+/// Filled by the interrupt handlers, drained by [`ButtonInput`].
+///
+/// All GPIO interrupts are dispatched one after another by the ISR service,
+/// so there is only ever one producer.
+static EVENTS: EventQueue<RawEvent, 32> = EventQueue::new();
 
+/// Sets up a button pin whose edges call `callback` and then wake the main loop through `notifier`
 pub fn setup_button<'d>(
     pin: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
-    callback: impl FnMut() + Send + 'static,
+    mut callback: impl FnMut() + Send + 'static,
+    notifier: Arc<Notifier>,
 ) -> Result<PinDriver<'d, impl Pin, Input>, EspError> {
     // Create a new PinDriver for GPIO4 configured as an input pin
     let mut driver = PinDriver::input(pin)?;
     // Enable an internal pull-up resistor on GPIO4
     driver.set_pull(Pull::Up)?;
-    // Set the interrupt to trigger on a positive edge (low → high transition)
-    driver.set_interrupt_type(gpio::InterruptType::NegEdge)?;
+    // Trigger on both edges, high → low is a press and low → high a release
+    driver.set_interrupt_type(gpio::InterruptType::AnyEdge)?;
     // Subscribe the GPIO4 interrupt to call the function `gipo_04` when triggered
     // `unsafe` is needed because we are passing a raw function pointer
-    unsafe { driver.subscribe(callback)? };
+    unsafe {
+        driver.subscribe(move || {
+            callback();
+            notifier.notify_and_yield(NonZeroU32::MIN);
+        })?
+    };
     // Enable interrupts for this pin
     driver.enable_interrupt()?;
 
     Ok(driver)
 }
 
+/// Records the current level of the pin. Runs in interrupt context.
+fn record(button: Button, gpio: i32) {
+    // Safety: both functions may be called from an interrupt handler
+    let (level, micros) = unsafe { (gpio_get_level(gpio), esp_timer_get_time()) };
+    let event = RawEvent {
+        button,
+        // The buttons pull the pin to ground
+        pressed: level == 0,
+        micros,
+    };
+
+    // Safety: the interrupt handlers are the only producer.
+    // Events are dropped when the main loop falls far behind.
+    let _ = unsafe { EVENTS.push(event) };
+}
+
 /// Queue Push for Button 1 (MoveLeft)
 pub fn gpio_04() {
-    BUTTON_LEFT.store(true, Ordering::SeqCst);
+    record(Button::Left, 4);
 }
 
 /// Queue Push for Button 2 (MoveRight)
 pub fn gpio_05() {
-    BUTTON_RIGHT.store(true, Ordering::SeqCst);
+    record(Button::Right, 5);
 }
 
 /// Queue Push for Button 3 (MoveDown)
 pub fn gpio_06() {
-    BUTTON_DOWN.store(true, Ordering::SeqCst);
+    record(Button::Down, 6);
 }
 
 /// Queue Push for Button 4 (Rotate)
 pub fn gpio_07() {
-    BUTTON_ROTATE.store(true, Ordering::SeqCst);
+    record(Button::Rotate, 7);
+}
+
+/// Hands out the raw edges recorded by the interrupt handlers
+pub struct ButtonInput {
+    /// The same moment in both time bases, to convert timer ticks into an [`Instant`]
+    reference: (Instant, i64),
+}
+
+impl ButtonInput {
+    pub fn new() -> Self {
+        // Safety: reading the timer has no preconditions
+        let micros = unsafe { esp_timer_get_time() };
+        Self {
+            reference: (Instant::now(), micros),
+        }
+    }
+
+    /// The current time in the time base of the events
+    pub fn now(&self) -> Instant {
+        // Safety: reading the timer has no preconditions
+        self.instant(unsafe { esp_timer_get_time() })
+    }
+
+    fn instant(&self, micros: i64) -> Instant {
+        let (instant, reference_micros) = self.reference;
+        instant + Duration::from_micros(micros.saturating_sub(reference_micros).max(0) as u64)
+    }
+}
+
+impl InputSource for ButtonInput {
+    fn poll(&mut self) -> Option<InputEvent> {
+        // Safety: the main loop is the only consumer
+        let raw = unsafe { EVENTS.pop() }?;
+        Some(InputEvent {
+            button: raw.button,
+            pressed: raw.pressed,
+            time: self.instant(raw.micros),
+        })
+    }
+}
+
+impl Default for ButtonInput {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,22 @@
+use esp_idf_svc::nvs::{EspNvs, NvsDefault};
+use game::input::KeyMap;
+
+/// Stored in the same namespace as the rules
+const NVS_KEY: &str = "keys_v1";
+
+pub fn save_keymap(
+    nvs: &mut EspNvs<NvsDefault>,
+    keymap: &KeyMap,
+) -> Result<(), Box<dyn std::error::Error>> {
+    nvs.set_str(NVS_KEY, &keymap.serialize())?;
+    Ok(())
+}
+
+pub fn load_keymap(nvs: &mut EspNvs<NvsDefault>) -> Result<KeyMap, Box<dyn std::error::Error>> {
+    // Falls back to the default bindings if nothing valid was stored yet
+    if let Some(serialized_keymap) = nvs.get_str(NVS_KEY, &mut [0u8; 1024])? {
+        Ok(KeyMap::deserialize(serialized_keymap).unwrap_or_default())
+    } else {
+        Ok(KeyMap::default())
+    }
+}
@@ -0,0 +1,14 @@
+use esp_idf_svc::nvs::{EspNvs, NvsDefault};
+use game::display::Layout;
+
+/// Stored in the same namespace as the rules
+const NVS_KEY: &str = "layout_v1";
+
+pub fn load_layout(nvs: &mut EspNvs<NvsDefault>) -> Result<Layout, Box<dyn std::error::Error>> {
+    // Falls back to the prototype wiring if nothing valid was stored yet
+    if let Some(serialized_layout) = nvs.get_str(NVS_KEY, &mut [0u8; 512])? {
+        Ok(Layout::deserialize(serialized_layout).unwrap_or_default())
+    } else {
+        Ok(Layout::default())
+    }
+}
@@ -1,23 +1,39 @@
+use esp_idf_hal::delay::TickType;
 use esp_idf_hal::peripherals::Peripherals;
 use esp_idf_hal::spi::{SpiDeviceDriver, SpiDriver};
+use esp_idf_hal::task::notification::Notification;
 use esp_idf_svc::nvs::{EspNvs, EspNvsPartition, NvsDefault};
-use game::display::Max72xx;
-use game::display::render::render;
-use game::logic::{ButtonAction, GameState, InStartState, StartMenuPhase};
-use std::sync::atomic::Ordering;
+use game::display::render::{render, render_stats};
+use game::display::{Display, MAX_DIGITS, Max72xx, SevenSegment};
+use game::input::{Controls, LevelCheck};
+use game::logic::{GameEvent, GameState, InStartState};
+use game::scheduler::TickScheduler;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 pub mod highscore;
 use highscore::{NVS_NAMESPACE, load_highscores, save_highscores};
 
+mod rules;
+use rules::load_rules;
+
+mod keymap;
+use keymap::{load_keymap, save_keymap};
+
+mod layout;
+use layout::load_layout;
+
 mod website;
 use website::WifiServer;
 
 mod input;
-use input::{gpio_04, gpio_05, gpio_06, gpio_07, setup_button};
+use input::{ButtonInput, gpio_04, gpio_05, gpio_06, gpio_07, setup_button};
+
+/// Game logic updates per second
+const TICK_RATE: u32 = 60;
 
-use crate::input::{BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_ROTATE};
+/// How often the frame time statistics are logged
+const STATS_INTERVAL_SECONDS: u32 = 10;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // It is necessary to call this function once. Otherwise some patches to the runtime
@@ -32,81 +48,145 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // NVS partition for WLAN configuration
     let partition = EspNvsPartition::<NvsDefault>::take().unwrap();
     let mut nvs = EspNvs::new(partition.clone(), NVS_NAMESPACE, true).unwrap();
+    let mut settings_nvs = EspNvs::new(partition.clone(), rules::NVS_NAMESPACE, true).unwrap();
 
-    // Webserver initialization with score from memory
+    // Webserver initialization with score and key map from memory
     let highscores = Arc::new(Mutex::new(load_highscores(&mut nvs)?));
+    let keymap = Arc::new(Mutex::new(load_keymap(&mut settings_nvs)?));
     let _wifi_server = WifiServer::new(
         peripherals.modem,
         partition.clone(),
         Arc::clone(&highscores),
+        Arc::clone(&keymap),
     )?;
 
-    let mut display = {
-        // Initialize SPI2
-        let spi_driver = SpiDriver::new(
-            peripherals.spi2,
-            peripherals.pins.gpio12,       // SCLK (FSPICLK)
-            peripherals.pins.gpio11,       // MOSI (FSPID)
-            Some(peripherals.pins.gpio13), // MISO (FSPIQ), not used
-            &Default::default(),
-        )?;
+    // Initialize SPI2, shared by the matrix and the 7-segment display
+    let spi_driver = SpiDriver::new(
+        peripherals.spi2,
+        peripherals.pins.gpio12,       // SCLK (FSPICLK)
+        peripherals.pins.gpio11,       // MOSI (FSPID)
+        Some(peripherals.pins.gpio13), // MISO (FSPIQ), not used
+        &Default::default(),
+    )?;
 
+    let mut display = {
         // Chip Select pin for the cascaded MAX72xx devices
         let cs_pin = peripherals.pins.gpio10;
-        let spi = SpiDeviceDriver::new(spi_driver, Some(cs_pin), &Default::default())?;
+        let spi = SpiDeviceDriver::new(&spi_driver, Some(cs_pin), &Default::default())?;
 
-        Max72xx::new(spi, 4)
+        // Module arrangement of the board, four stacked modules unless configured otherwise
+        Max72xx::with_layout(spi, load_layout(&mut settings_nvs)?)
     };
     display.reset()?;
+    // Rewrite one control register per second to recover from glitches
+    display.set_reinit_interval(Some(TICK_RATE));
+
+    // Optional MAX7219 with 8 digits showing level, lines and score
+    let mut stats_display = {
+        let cs_pin = peripherals.pins.gpio9;
+        let spi = SpiDeviceDriver::new(&spi_driver, Some(cs_pin), &Default::default())?;
+        SevenSegment::new(spi, MAX_DIGITS)
+    };
+    if let Err(error) = stats_display.reset() {
+        // The game runs just as well without it
+        log::warn!("Fehler beim Initialisieren der Punkteanzeige: {error:?}");
+    }
 
-    let mut button1 = setup_button(peripherals.pins.gpio4, gpio_04)?;
-    let mut button2 = setup_button(peripherals.pins.gpio5, gpio_05)?;
-    let mut button3 = setup_button(peripherals.pins.gpio6, gpio_06)?;
-    let mut button4 = setup_button(peripherals.pins.gpio7, gpio_07)?;
-
-    let mut game_state = GameState::StartMenu(InStartState {
-        phase: StartMenuPhase::ButtonReleased,
-        last_update: Instant::now() - Duration::from_millis(1000),
-    });
+    // Button edges wake the main loop while it sleeps between ticks
+    let notification = Notification::new();
+    let mut button1 = setup_button(peripherals.pins.gpio4, gpio_04, notification.notifier())?;
+    let mut button2 = setup_button(peripherals.pins.gpio5, gpio_05, notification.notifier())?;
+    let mut button3 = setup_button(peripherals.pins.gpio6, gpio_06, notification.notifier())?;
+    let mut button4 = setup_button(peripherals.pins.gpio7, gpio_07, notification.notifier())?;
+
+    let rules = load_rules(&mut settings_nvs)?;
+    let mut input = LevelCheck::new(ButtonInput::new());
+    let mut controls = Controls::new(rules.debounce, keymap.lock().unwrap().clone());
+    let mut game_state = GameState::StartMenu(
+        InStartState::new(rules, Instant::now())
+            .with_highscores(highscores.lock().unwrap().clone()),
+    );
 
     log::info!("{highscores:?}");
     while highscores.try_lock().is_err() {}
 
-    let mut last_interaction = Instant::now() - Duration::from_millis(1000);
-    let mut button_action = None;
+    let mut events = Vec::new();
+    let mut scheduler = TickScheduler::with_rate(TICK_RATE, Instant::now());
 
     loop {
-        // Collect input
-        button1.enable_interrupt()?;
-        button2.enable_interrupt()?;
-        button3.enable_interrupt()?;
-        button4.enable_interrupt()?;
-
-        for (state, action) in [
-            (&BUTTON_LEFT, ButtonAction::MoveLeft),
-            (&BUTTON_RIGHT, ButtonAction::MoveRight),
-            (&BUTTON_DOWN, ButtonAction::MoveDown),
-            (&BUTTON_ROTATE, ButtonAction::Rotate),
-        ] {
-            if !state.swap(false, Ordering::SeqCst) {
-                continue;
+        // Sleep until the next tick, which lets FreeRTOS run other tasks or idle.
+        // Every button edge wakes us up early to re-arm the interrupts,
+        // so no edge is lost while we sleep. The edges carry their own
+        // time stamps and are handled with the next tick.
+        loop {
+            // Interrupts are disabled after each trigger
+            button1.enable_interrupt()?;
+            button2.enable_interrupt()?;
+            button3.enable_interrupt()?;
+            button4.enable_interrupt()?;
+            // Edges while an interrupt was still disabled are lost, the levels catch up on them
+            let levels = [
+                button1.is_low(),
+                button2.is_low(),
+                button3.is_low(),
+                button4.is_low(),
+            ];
+            input.sample(levels, input.source().now());
+
+            let timeout = scheduler.time_until_tick(Instant::now());
+            if timeout.is_zero() {
+                break;
             }
+            notification.wait(TickType::from(timeout).ticks());
+        }
+        scheduler.begin_tick(Instant::now());
+
+        // Take over a key map edited on the website
+        if let Ok(keymap) = keymap.try_lock()
+            && *keymap != *controls.keymap()
+        {
+            controls.set_keymap(keymap.clone());
+            save_keymap(&mut settings_nvs, &keymap).unwrap();
+        }
 
-            // Debounce
-            if last_interaction.elapsed() > Duration::from_millis(150) {
-                last_interaction = Instant::now();
-                button_action = Some(action);
+        game_state = game_state.update_from(&mut input, &mut controls, Instant::now(), &mut events);
+
+        for event in events.drain(..) {
+            // The game keeps the table, the website and the flash get a copy
+            if let GameEvent::NewHighscore { .. } = event {
+                let mut highscores = highscores.lock().unwrap();
+                *highscores = game_state.highscores().clone();
+                save_highscores(&mut nvs, &highscores).unwrap();
             }
         }
 
-        game_state = game_state.update(button_action.take(), Instant::now(), |score| {
-            let mut highscores = highscores.lock().unwrap();
-            highscores.add_score(score);
-            save_highscores(&mut nvs, &highscores).unwrap();
-        });
+        // Only changed rows are sent, so this is cheap when nothing changed
+        render(&game_state, &mut display);
+        let changed = display.is_dirty();
+        if let Err(error) = display.flush() {
+            // The chain is re-initialised with the next flush
+            log::warn!("Fehler beim Übertragen an die Anzeige: {error:?}");
+        }
 
-        render(&mut game_state, &mut display);
+        render_stats(&game_state, &mut stats_display);
+        if let Err(error) = stats_display.flush() {
+            log::warn!("Fehler beim Übertragen an die Punkteanzeige: {error:?}");
+        }
 
-        display.transfer_bitmap()?;
+        scheduler.end_tick(Instant::now(), changed);
+
+        if scheduler.stats().ticks >= TICK_RATE * STATS_INTERVAL_SECONDS {
+            let stats = scheduler.take_stats();
+            log::info!(
+                "{} Ticks, {} Bilder, {} verpasst, Rechenzeit min {:?} / avg {:?} / max {:?}, Last {:.1}%",
+                stats.ticks,
+                stats.frames,
+                stats.missed,
+                stats.min_busy,
+                stats.average_busy(),
+                stats.max_busy,
+                stats.load(scheduler.interval()) * 100.0,
+            );
+        }
     }
 }
@@ -0,0 +1,14 @@
+use esp_idf_svc::nvs::{EspNvs, NvsDefault};
+use game::logic::RuleSet;
+
+pub const NVS_NAMESPACE: &str = "settings";
+const NVS_KEY: &str = "rules_v1";
+
+pub fn load_rules(nvs: &mut EspNvs<NvsDefault>) -> Result<RuleSet, Box<dyn std::error::Error>> {
+    // Falls back to the classic rules if nothing valid was stored yet
+    if let Some(serialized_rules) = nvs.get_str(NVS_KEY, &mut [0u8; 512])? {
+        Ok(RuleSet::deserialize(serialized_rules).unwrap_or_default())
+    } else {
+        Ok(RuleSet::default())
+    }
+}
@@ -1,16 +1,20 @@
-use crate::highscore::Highscores;
-use esp_idf_hal::io::Write;
+use esp_idf_hal::io::{Read, Write};
 use esp_idf_hal::modem::Modem;
 use esp_idf_svc::eventloop::EspSystemEventLoop;
 use esp_idf_svc::http::server::{Configuration, EspHttpServer};
 use esp_idf_svc::nvs::EspNvsPartition;
 use esp_idf_svc::wifi::{AuthMethod, BlockingWifi, EspWifi};
+use game::input::{Button, KeyMap};
+use game::logic::{ButtonAction, Highscores};
 use std::sync::{Arc, Mutex};
 
 // Configuration for the WLAN access point
 const SSID: &str = "ESP32-Tetris";
 const PASSWORD: &str = "tetris123";
 
+// Upper limit for a submitted key map, which is only a few short lines
+const MAX_KEYMAP_LEN: usize = 1024;
+
 // A structure that holds all necessary network services together.
 // As long as an instance of this structure exists, the Wi-Fi and server remain active.
 pub struct WifiServer<'a> {
@@ -25,10 +29,12 @@ impl<'a> WifiServer<'a> {
     /// - `modem`: The ESP32's Wi-Fi modem (taken from `Peripherals`)
     /// - `nvs`: Non-Volatile Storage Defeault Partition for storing Wi-Fi information
     /// - `highscores`: Thread-safe access to the highscore list
+    /// - `keymap`: Thread-safe access to the key map, replaced when edited on the page
     pub fn new(
         modem: Modem,
         nvs: EspNvsPartition<esp_idf_svc::nvs::NvsDefault>,
         highscores: Arc<Mutex<Highscores>>,
+        keymap: Arc<Mutex<KeyMap>>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         // Get system event loop (only possible once per program)
         let sys_loop = EspSystemEventLoop::take()?;
@@ -80,6 +86,60 @@ impl<'a> WifiServer<'a> {
             },
         )?;
 
+        // Page for editing the key map
+        let keymap_page = Arc::clone(&keymap);
+        server.fn_handler(
+            "/keys",
+            esp_idf_svc::http::Method::Get,
+            move |request| -> Result<(), Box<dyn std::error::Error>> {
+                let html_response = generate_keymap_html(&keymap_page.lock().unwrap());
+                let mut response = request.into_ok_response()?;
+                response.write_all(html_response.as_bytes())?;
+                Ok(())
+            },
+        )?;
+
+        // Receives the edited key map as plain text
+        server.fn_handler(
+            "/keys",
+            esp_idf_svc::http::Method::Post,
+            move |mut request| -> Result<(), Box<dyn std::error::Error>> {
+                let mut body = Vec::new();
+                let mut buffer = [0u8; 128];
+                loop {
+                    let len = request.read(&mut buffer)?;
+                    if len == 0 {
+                        break;
+                    }
+                    body.extend_from_slice(&buffer[..len]);
+                    if body.len() > MAX_KEYMAP_LEN {
+                        let mut response =
+                            request.into_response(413, Some("Payload Too Large"), &[])?;
+                        response.write_all("Tastenbelegung ist zu lang".as_bytes())?;
+                        return Ok(());
+                    }
+                }
+
+                // The game loop picks up the new map and stores it
+                let parsed = std::str::from_utf8(&body)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| KeyMap::deserialize(text).map_err(|error| error.to_string()));
+                match parsed {
+                    Ok(new_keymap) => {
+                        *keymap.lock().unwrap() = new_keymap;
+                        let mut response = request.into_ok_response()?;
+                        response.write_all("Gespeichert".as_bytes())?;
+                    }
+                    Err(error) => {
+                        log::warn!("Ungültige Tastenbelegung: {error}");
+                        let mut response = request.into_response(400, Some("Bad Request"), &[])?;
+                        response.write_all(format!("Fehler: {error}").as_bytes())?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
         // Return the structure containing both the wifi driver and the server.
         Ok(Self {
             _wifi: wifi,
@@ -95,17 +155,53 @@ fn generate_html(highscores: &Highscores) -> String {
     let mut body = String::new();
 
     // Insert high scores dynamically
-    if highscores.scores.is_empty() {
+    if highscores.entries.is_empty() {
         body.push_str("<p>Bisher keine Highscores aufgezeichnet.</p>");
     } else {
         body.push_str("<ol>");
-        for score in highscores.scores.iter() {
-            body.push_str(&format!("<li>Platz: {score} Punkte</li>"));
+        for entry in highscores.entries.iter() {
+            let initials = String::from_iter(entry.initials);
+            body.push_str(&format!("<li>{initials}: {} Punkte</li>", entry.score));
         }
         body.push_str("</ol>");
     }
+    body.push_str("<p><a href='/keys'>Tastenbelegung ändern</a></p>");
+
+    generate_page("Tetris Highscores", &body)
+}
+
+/// Generates the HTML code for the key map editor.
+///
+/// The map is edited as text, one `trigger=action` line per binding.
+fn generate_keymap_html(keymap: &KeyMap) -> String {
+    let buttons = Button::ALL.map(Button::name).join(", ");
+    let actions = ButtonAction::ALL.map(ButtonAction::name).join(", ");
+
+    let body = format!(
+        r#"
+        <p>Eine Zeile pro Belegung, z.B. <code>left=move_left</code>,
+        <code>long rotate=hold</code> oder <code>left+right=pause</code>.
+        Tasten mit langem Druck oder in einer Kombination reagieren verzögert.</p>
+        <p>Tasten: {buttons}<br>Aktionen: {actions}</p>
+        <textarea id="keys" rows="10" cols="30">{keys}</textarea><br>
+        <button onclick="save()">Speichern</button>
+        <p id="status"></p>
+        <p><a href='/'>Zurück zu den Highscores</a></p>
+        <script>
+            async function save() {{
+                const response = await fetch('/keys', {{ method: 'POST', body: document.getElementById('keys').value }});
+                document.getElementById('status').textContent = await response.text();
+            }}
+        </script>
+        "#,
+        keys = keymap.serialize(),
+    );
+
+    generate_page("Tastenbelegung", &body)
+}
 
-    // HTML page structure
+/// Wraps the body into the HTML page structure shared by all pages
+fn generate_page(title: &str, body: &str) -> String {
     format!(
         r#"
         <!DOCTYPE html>
@@ -113,18 +209,19 @@ fn generate_html(highscores: &Highscores) -> String {
         <head>
             <meta charset="utf-8">
             <meta name='viewport' content='width=device-width, initial-scale=1'>
-            <title>ESP32 Tetris Highscores</title>
+            <title>ESP32 {title}</title>
             <style>
                 body {{ font-family: Arial, sans-serif; background-color: #282c34; color: #ffffff; display: flex; justify-content: center; align-items: center; height: 100vh; margin: 0; }}
                 .container {{ background-color: #20232a; padding: 2rem; border-radius: 8px; box-shadow: 0 4px 8px rgba(0,0,0,0.2); text-align: center; }}
                 h1 {{ color: #61dafb; }}
+                a {{ color: #61dafb; }}
                 ol {{ list-style-position: inside; padding: 0; }}
                 li {{ background-color: #3c4049; margin: 0.5rem 0; padding: 0.5rem; border-radius: 4px; }}
             </style>
         </head>
         <body>
             <div class="container">
-                <h1>Tetris Highscores</h1>
+                <h1>{title}</h1>
                 {body}
             </div>
         </body>
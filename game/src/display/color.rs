@@ -0,0 +1,100 @@
+/// A colour with 8 bits per channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub const fn is_black(self) -> bool {
+        self.r == 0 && self.g == 0 && self.b == 0
+    }
+
+    /// Value of the brightest channel
+    pub const fn max_channel(self) -> u8 {
+        let rg = if self.r > self.g { self.r } else { self.g };
+        if rg > self.b { rg } else { self.b }
+    }
+
+    /// Scales all channels by `factor / 255`
+    pub const fn scale(self, factor: u8) -> Self {
+        const fn channel(value: u8, factor: u8) -> u8 {
+            ((value as u16 * factor as u16 + 127) / 255) as u8
+        }
+        Self::new(
+            channel(self.r, factor),
+            channel(self.g, factor),
+            channel(self.b, factor),
+        )
+    }
+}
+
+/// Colours for the parts of the game on a colour display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    /// Colour of each piece, indexed by the letter of its name from `A` to `Z`
+    pub pieces: [Rgb; 26],
+    /// Blocks that do not belong to a piece, e.g. the layout of a puzzle
+    pub garbage: Rgb,
+    /// Preview of where the current piece lands
+    pub ghost: Rgb,
+    /// The divider and the pause sign
+    pub hud: Rgb,
+    /// Menus, text and the score
+    pub text: Rgb,
+}
+
+impl Palette {
+    /// Colour of the piece with the given name
+    pub fn piece(&self, name: char) -> Rgb {
+        match name.to_ascii_uppercase() {
+            letter @ 'A'..='Z' => self.pieces[(letter as u8 - b'A') as usize],
+            _ => self.garbage,
+        }
+    }
+}
+
+/// The usual colours for the tetrominoes and distinct ones for the other pieces.
+/// The ghost is kept too dim to show up on a [`super::Monochrome`] display,
+/// where it would look like blocks that already landed.
+impl Default for Palette {
+    fn default() -> Self {
+        let garbage = Rgb::new(200, 200, 200);
+        let mut pieces = [garbage; 26];
+        for (name, color) in [
+            ('F', Rgb::new(255, 64, 160)),
+            ('I', Rgb::new(0, 255, 255)),
+            ('J', Rgb::new(0, 64, 255)),
+            ('L', Rgb::new(255, 128, 0)),
+            ('N', Rgb::new(0, 160, 128)),
+            ('O', Rgb::new(255, 255, 0)),
+            ('P', Rgb::new(160, 255, 0)),
+            ('S', Rgb::new(0, 255, 0)),
+            ('T', Rgb::new(160, 0, 255)),
+            ('U', Rgb::new(200, 120, 40)),
+            ('V', Rgb::new(64, 160, 255)),
+            ('W', Rgb::new(255, 0, 255)),
+            ('X', Rgb::new(255, 128, 128)),
+            ('Y', Rgb::new(128, 128, 255)),
+            ('Z', Rgb::new(255, 0, 0)),
+        ] {
+            pieces[(name as u8 - b'A') as usize] = color;
+        }
+
+        Self {
+            pieces,
+            garbage,
+            ghost: Rgb::new(112, 112, 112),
+            hud: Rgb::WHITE,
+            text: Rgb::WHITE,
+        }
+    }
+}
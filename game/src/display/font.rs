@@ -0,0 +1,850 @@
+use super::Display;
+
+/// Which way text runs on a display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    /// Characters next to each other, from left to right
+    Horizontal,
+    /// Upright characters below each other, e.g. on a tall 8x32 display
+    Vertical,
+    /// Characters turned clockwise from top to bottom, read with the head tilted
+    /// to the right, so longer words fit along the tall side of a display
+    Rotated,
+}
+
+/// A bitmap font for ASCII text.
+///
+/// Each glyph is stored as rows from the top, the leftmost pixel of a row is its
+/// highest bit. Fonts without lowercase letters show them in uppercase.
+#[derive(Debug, Clone, Copy)]
+pub struct Font {
+    width: u8,
+    height: u8,
+    /// Blank pixels between two characters, in the direction the text runs
+    spacing: u8,
+    /// Whether characters only take the columns their pixels need
+    proportional: bool,
+    /// Character of the first glyph
+    first: char,
+    glyphs: &'static [u8],
+}
+
+/// Printable ASCII in 8x8 cells, the last column is left blank so characters
+/// can sit right next to each other. Only descenders reach into the last row.
+pub const FONT_8X8: Font = Font::new(8, 8, ' ', &GLYPHS_8X8);
+
+/// Digits, uppercase letters and punctuation in 3x5 cells, narrow characters
+/// like `1` or `.` take less room
+pub const FONT_3X5: Font = Font::new(3, 5, ' ', &GLYPHS_3X5)
+    .with_spacing(1)
+    .with_proportional(true);
+
+impl Font {
+    /// A monospaced font, `glyphs` holds `height` rows per character starting with `first`
+    pub const fn new(width: u8, height: u8, first: char, glyphs: &'static [u8]) -> Self {
+        Self {
+            width,
+            height,
+            spacing: 0,
+            proportional: false,
+            first,
+            glyphs,
+        }
+    }
+
+    pub const fn with_spacing(mut self, spacing: u8) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Lets every character take only the columns its pixels need, blank
+    /// characters take half the width
+    pub const fn with_proportional(mut self, proportional: bool) -> Self {
+        self.proportional = proportional;
+        self
+    }
+
+    /// Size of a character cell
+    pub const fn size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+
+    /// Rows of the glyph for a character, `None` when the font has no glyph for it
+    pub fn glyph(&self, c: char) -> Option<&'static [u8]> {
+        let glyphs = self.glyphs;
+        let height = self.height as usize;
+        let find = |c: char| {
+            let index = (c as u32).checked_sub(self.first as u32)? as usize;
+            glyphs.get(index * height..(index + 1) * height)
+        };
+        find(c).or_else(|| find(c.to_ascii_uppercase()))
+    }
+
+    /// Width of a character in pixels, without the spacing
+    pub fn char_width(&self, c: char) -> u8 {
+        self.columns(c).1
+    }
+
+    /// Length of the text in pixels along the direction it runs
+    pub fn text_length(&self, text: &str, direction: TextDirection) -> u16 {
+        let mut length = 0;
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 {
+                length += self.spacing as u16;
+            }
+            length += self.advance(c, direction) as u16;
+        }
+        length
+    }
+
+    /// First column and number of columns a character takes
+    fn columns(&self, c: char) -> (u8, u8) {
+        if !self.proportional {
+            return (0, self.width);
+        }
+        let used = self
+            .glyph(c)
+            .map_or(0, |rows| rows.iter().fold(0, |used, row| used | row));
+        if used == 0 {
+            return (0, self.width.div_ceil(2));
+        }
+        let first = used.leading_zeros() as u8;
+        (first, 8 - used.trailing_zeros() as u8 - first)
+    }
+
+    /// Room a character takes along the direction the text runs, without the spacing
+    fn advance(&self, c: char, direction: TextDirection) -> u8 {
+        match direction {
+            TextDirection::Vertical => self.height,
+            TextDirection::Horizontal | TextDirection::Rotated => self.char_width(c),
+        }
+    }
+}
+
+/// Draws the text with its top left corner at `x` and `y`. Only lit pixels are set,
+/// anything outside of the display is cut off, so text can be scrolled across it.
+pub fn draw_text(
+    display: &mut impl Display,
+    font: &Font,
+    text: &str,
+    x: i16,
+    y: i16,
+    direction: TextDirection,
+) {
+    let (width, height) = display.size();
+    let mut set_pixel = |x: i16, y: i16| {
+        if (0..width as i16).contains(&x) && (0..height as i16).contains(&y) {
+            display.set_pixel(x as u8, y as u8, true);
+        }
+    };
+
+    let mut position = 0;
+    for c in text.chars() {
+        let (first, columns) = font.columns(c);
+        for (row, bits) in font.glyph(c).unwrap_or_default().iter().enumerate() {
+            for column in 0..columns {
+                if bits & (0b1000_0000 >> (first + column)) == 0 {
+                    continue;
+                }
+                let (row, column) = (row as i16, column as i16);
+                match direction {
+                    TextDirection::Horizontal => set_pixel(x + position + column, y + row),
+                    TextDirection::Vertical => set_pixel(x + column, y + position + row),
+                    TextDirection::Rotated => {
+                        set_pixel(x + font.height as i16 - 1 - row, y + position + column)
+                    }
+                }
+            }
+        }
+        position += font.advance(c, direction) as i16 + font.spacing as i16;
+    }
+}
+
+const GLYPHS_8X8: [u8; 95 * 8] = [
+    // ' '
+    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // '!'
+    0b00011000, 0b00111100, 0b00111100, 0b00011000, 0b00011000, 0b00000000, 0b00011000, 0b00000000,
+    // '"'
+    0b01101100, 0b01101100, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // '#'
+    0b01101100, 0b01101100, 0b11111110, 0b01101100, 0b11111110, 0b01101100, 0b01101100, 0b00000000,
+    // '$'
+    0b00110000, 0b01111100, 0b11000000, 0b01111000, 0b00001100, 0b11111000, 0b00110000, 0b00000000,
+    // '%'
+    0b00000000, 0b11000110, 0b11001100, 0b00011000, 0b00110000, 0b01100110, 0b11000110, 0b00000000,
+    // '&'
+    0b00111000, 0b01101100, 0b00111000, 0b01110110, 0b11011100, 0b11001100, 0b01110110, 0b00000000,
+    // '\''
+    0b01100000, 0b01100000, 0b11000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // '('
+    0b00011000, 0b00110000, 0b01100000, 0b01100000, 0b01100000, 0b00110000, 0b00011000, 0b00000000,
+    // ')'
+    0b01100000, 0b00110000, 0b00011000, 0b00011000, 0b00011000, 0b00110000, 0b01100000, 0b00000000,
+    // '*'
+    0b00000000, 0b01100110, 0b00111100, 0b01111110, 0b00111100, 0b01100110, 0b00000000, 0b00000000,
+    // '+'
+    0b00000000, 0b00110000, 0b00110000, 0b11111100, 0b00110000, 0b00110000, 0b00000000, 0b00000000,
+    // ','
+    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00110000, 0b00110000, 0b01100000,
+    // '-'
+    0b00000000, 0b00000000, 0b00000000, 0b01111110, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // '.'
+    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00110000, 0b00110000, 0b00000000,
+    // '/'
+    0b00000110, 0b00001100, 0b00011000, 0b00110000, 0b01100000, 0b11000000, 0b10000000, 0b00000000,
+    // '0'
+    0b00111100, 0b01100110, 0b01101110, 0b01110110, 0b01100110, 0b01100110, 0b00111100, 0b00000000,
+    // '1'
+    0b00011000, 0b00111000, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00111100, 0b00000000,
+    // '2'
+    0b00111100, 0b01100110, 0b00000110, 0b00001100, 0b00011000, 0b01100000, 0b01111110, 0b00000000,
+    // '3'
+    0b00111100, 0b01100110, 0b00000110, 0b00011100, 0b00000110, 0b01100110, 0b00111100, 0b00000000,
+    // '4'
+    0b00001100, 0b00011100, 0b00101100, 0b01001100, 0b01111110, 0b00001100, 0b00001100, 0b00000000,
+    // '5'
+    0b01111110, 0b01100000, 0b01111100, 0b00000110, 0b00000110, 0b01100110, 0b00111100, 0b00000000,
+    // '6'
+    0b00111100, 0b01100110, 0b01100000, 0b01111100, 0b01100110, 0b01100110, 0b00111100, 0b00000000,
+    // '7'
+    0b01111110, 0b01100110, 0b00000110, 0b00001100, 0b00011000, 0b00011000, 0b00011000, 0b00000000,
+    // '8'
+    0b00111100, 0b01100110, 0b01100110, 0b00111100, 0b01100110, 0b01100110, 0b00111100, 0b00000000,
+    // '9'
+    0b00111100, 0b01100110, 0b01100110, 0b00111110, 0b00000110, 0b01100110, 0b00111100, 0b00000000,
+    // ':'
+    0b00000000, 0b00110000, 0b00110000, 0b00000000, 0b00000000, 0b00110000, 0b00110000, 0b00000000,
+    // ';'
+    0b00000000, 0b00110000, 0b00110000, 0b00000000, 0b00000000, 0b00110000, 0b00110000, 0b01100000,
+    // '<'
+    0b00011000, 0b00110000, 0b01100000, 0b11000000, 0b01100000, 0b00110000, 0b00011000, 0b00000000,
+    // '='
+    0b00000000, 0b00000000, 0b11111100, 0b00000000, 0b00000000, 0b11111100, 0b00000000, 0b00000000,
+    // '>'
+    0b01100000, 0b00110000, 0b00011000, 0b00001100, 0b00011000, 0b00110000, 0b01100000, 0b00000000,
+    // '?'
+    0b01111000, 0b11001100, 0b00001100, 0b00011000, 0b00110000, 0b00000000, 0b00110000, 0b00000000,
+    // '@'
+    0b01111100, 0b11000110, 0b11011110, 0b11011110, 0b11011110, 0b11000000, 0b01111000, 0b00000000,
+    // 'A'
+    0b00011000, 0b00111100, 0b01100110, 0b01100110, 0b01111110, 0b01100110, 0b01100110, 0b00000000,
+    // 'B'
+    0b11111100, 0b01100110, 0b01100110, 0b01111100, 0b01100110, 0b01100110, 0b11111100, 0b00000000,
+    // 'C'
+    0b00111100, 0b01100110, 0b11000000, 0b11000000, 0b11000000, 0b01100110, 0b00111100, 0b00000000,
+    // 'D'
+    0b11111000, 0b01101100, 0b01100110, 0b01100110, 0b01100110, 0b01101100, 0b11111000, 0b00000000,
+    // 'E'
+    0b11111110, 0b01100010, 0b01101000, 0b01111000, 0b01101000, 0b01100010, 0b11111110, 0b00000000,
+    // 'F'
+    0b11111110, 0b01100010, 0b01101000, 0b01111000, 0b01101000, 0b01100000, 0b11110000, 0b00000000,
+    // 'G'
+    0b00111100, 0b01100110, 0b11000000, 0b11000000, 0b11001110, 0b01100110, 0b00111110, 0b00000000,
+    // 'H'
+    0b01100110, 0b01100110, 0b01100110, 0b01111110, 0b01100110, 0b01100110, 0b01100110, 0b00000000,
+    // 'I'
+    0b00111100, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00111100, 0b00000000,
+    // 'J'
+    0b00011110, 0b00001100, 0b00001100, 0b00001100, 0b11001100, 0b11001100, 0b01111000, 0b00000000,
+    // 'K'
+    0b11100110, 0b01100110, 0b01101100, 0b01111000, 0b01101100, 0b01100110, 0b11100110, 0b00000000,
+    // 'L'
+    0b11110000, 0b01100000, 0b01100000, 0b01100000, 0b01100010, 0b01100110, 0b11111110, 0b00000000,
+    // 'M'
+    0b11000110, 0b11101110, 0b11111110, 0b11111110, 0b11010110, 0b11000110, 0b11000110, 0b00000000,
+    // 'N'
+    0b11000110, 0b11100110, 0b11110110, 0b11011110, 0b11001110, 0b11000110, 0b11000110, 0b00000000,
+    // 'O'
+    0b00111000, 0b01101100, 0b11000110, 0b11000110, 0b11000110, 0b01101100, 0b00111000, 0b00000000,
+    // 'P'
+    0b11111100, 0b01100110, 0b01100110, 0b01111100, 0b01100000, 0b01100000, 0b11110000, 0b00000000,
+    // 'Q'
+    0b00111100, 0b01100110, 0b01100110, 0b01100110, 0b01101110, 0b00111100, 0b00001110, 0b00000000,
+    // 'R'
+    0b11111100, 0b01100110, 0b01100110, 0b01111100, 0b01101100, 0b01100110, 0b11100110, 0b00000000,
+    // 'S'
+    0b00111100, 0b01100110, 0b01110000, 0b00111000, 0b00001110, 0b01100110, 0b00111100, 0b00000000,
+    // 'T'
+    0b01111110, 0b01011010, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00111100, 0b00000000,
+    // 'U'
+    0b01100110, 0b01100110, 0b01100110, 0b01100110, 0b01100110, 0b01100110, 0b01111110, 0b00000000,
+    // 'V'
+    0b01100110, 0b01100110, 0b01100110, 0b01100110, 0b01100110, 0b00111100, 0b00011000, 0b00000000,
+    // 'W'
+    0b11000110, 0b11000110, 0b11000110, 0b11010110, 0b11111110, 0b11101110, 0b11000110, 0b00000000,
+    // 'X'
+    0b11000110, 0b11000110, 0b01101100, 0b00111000, 0b00111000, 0b01101100, 0b11000110, 0b00000000,
+    // 'Y'
+    0b01100110, 0b01100110, 0b01100110, 0b00111100, 0b00011000, 0b00011000, 0b00111100, 0b00000000,
+    // 'Z'
+    0b11111110, 0b11000110, 0b10001100, 0b00011000, 0b00110010, 0b01100110, 0b11111110, 0b00000000,
+    // '['
+    0b01111000, 0b01100000, 0b01100000, 0b01100000, 0b01100000, 0b01100000, 0b01111000, 0b00000000,
+    // '\\'
+    0b11000000, 0b01100000, 0b00110000, 0b00011000, 0b00001100, 0b00000110, 0b00000010, 0b00000000,
+    // ']'
+    0b01111000, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b01111000, 0b00000000,
+    // '^'
+    0b00010000, 0b00111000, 0b01101100, 0b11000110, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // '_'
+    0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b11111110,
+    // '`'
+    0b00110000, 0b00110000, 0b00011000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+    // 'a'
+    0b00000000, 0b00000000, 0b01111000, 0b00001100, 0b01111100, 0b11001100, 0b01110110, 0b00000000,
+    // 'b'
+    0b11100000, 0b01100000, 0b01100000, 0b01111100, 0b01100110, 0b01100110, 0b11011100, 0b00000000,
+    // 'c'
+    0b00000000, 0b00000000, 0b01111000, 0b11001100, 0b11000000, 0b11001100, 0b01111000, 0b00000000,
+    // 'd'
+    0b00011100, 0b00001100, 0b00001100, 0b01111100, 0b11001100, 0b11001100, 0b01110110, 0b00000000,
+    // 'e'
+    0b00000000, 0b00000000, 0b01111000, 0b11001100, 0b11111100, 0b11000000, 0b01111000, 0b00000000,
+    // 'f'
+    0b00111000, 0b01101100, 0b01100000, 0b11110000, 0b01100000, 0b01100000, 0b11110000, 0b00000000,
+    // 'g'
+    0b00000000, 0b00000000, 0b01110110, 0b11001100, 0b11001100, 0b01111100, 0b00001100, 0b11111000,
+    // 'h'
+    0b11100000, 0b01100000, 0b01101100, 0b01110110, 0b01100110, 0b01100110, 0b11100110, 0b00000000,
+    // 'i'
+    0b00110000, 0b00000000, 0b01110000, 0b00110000, 0b00110000, 0b00110000, 0b01111000, 0b00000000,
+    // 'j'
+    0b00001100, 0b00000000, 0b00001100, 0b00001100, 0b00001100, 0b11001100, 0b11001100, 0b01111000,
+    // 'k'
+    0b11100000, 0b01100000, 0b01100110, 0b01101100, 0b01111000, 0b01101100, 0b11100110, 0b00000000,
+    // 'l'
+    0b01110000, 0b00110000, 0b00110000, 0b00110000, 0b00110000, 0b00110000, 0b01111000, 0b00000000,
+    // 'm'
+    0b00000000, 0b00000000, 0b11001100, 0b11111110, 0b11111110, 0b11010110, 0b11000110, 0b00000000,
+    // 'n'
+    0b00000000, 0b00000000, 0b11111000, 0b11001100, 0b11001100, 0b11001100, 0b11001100, 0b00000000,
+    // 'o'
+    0b00000000, 0b00000000, 0b01111000, 0b11001100, 0b11001100, 0b11001100, 0b01111000, 0b00000000,
+    // 'p'
+    0b00000000, 0b00000000, 0b11011100, 0b01100110, 0b01100110, 0b01111100, 0b01100000, 0b11110000,
+    // 'q'
+    0b00000000, 0b00000000, 0b01110110, 0b11001100, 0b11001100, 0b01111100, 0b00001100, 0b00011110,
+    // 'r'
+    0b00000000, 0b00000000, 0b11011100, 0b01110110, 0b01100110, 0b01100000, 0b11110000, 0b00000000,
+    // 's'
+    0b00000000, 0b00000000, 0b01111100, 0b11000000, 0b01111000, 0b00001100, 0b11111000, 0b00000000,
+    // 't'
+    0b00010000, 0b00110000, 0b01111100, 0b00110000, 0b00110000, 0b00110100, 0b00011000, 0b00000000,
+    // 'u'
+    0b00000000, 0b00000000, 0b11001100, 0b11001100, 0b11001100, 0b11001100, 0b01110110, 0b00000000,
+    // 'v'
+    0b00000000, 0b00000000, 0b11001100, 0b11001100, 0b11001100, 0b01111000, 0b00110000, 0b00000000,
+    // 'w'
+    0b00000000, 0b00000000, 0b11000110, 0b11010110, 0b11111110, 0b11111110, 0b01101100, 0b00000000,
+    // 'x'
+    0b00000000, 0b00000000, 0b11000110, 0b01101100, 0b00111000, 0b01101100, 0b11000110, 0b00000000,
+    // 'y'
+    0b00000000, 0b00000000, 0b11001100, 0b11001100, 0b11001100, 0b01111100, 0b00001100, 0b11111000,
+    // 'z'
+    0b00000000, 0b00000000, 0b11111100, 0b10011000, 0b00110000, 0b01100100, 0b11111100, 0b00000000,
+    // '{'
+    0b00011100, 0b00110000, 0b00110000, 0b11100000, 0b00110000, 0b00110000, 0b00011100, 0b00000000,
+    // '|'
+    0b00011000, 0b00011000, 0b00011000, 0b00000000, 0b00011000, 0b00011000, 0b00011000, 0b00000000,
+    // '}'
+    0b11100000, 0b00110000, 0b00110000, 0b00011100, 0b00110000, 0b00110000, 0b11100000, 0b00000000,
+    // '~'
+    0b01110110, 0b11011100, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+];
+
+const GLYPHS_3X5: [u8; 64 * 5] = [
+    // ' '
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    // '!'
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    0b000_00000,
+    0b010_00000,
+    // '"'
+    0b101_00000,
+    0b101_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    // '#'
+    0b101_00000,
+    0b111_00000,
+    0b101_00000,
+    0b111_00000,
+    0b101_00000,
+    // '$'
+    0b011_00000,
+    0b110_00000,
+    0b010_00000,
+    0b011_00000,
+    0b110_00000,
+    // '%'
+    0b100_00000,
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    0b001_00000,
+    // '&'
+    0b010_00000,
+    0b101_00000,
+    0b010_00000,
+    0b101_00000,
+    0b011_00000,
+    // '\''
+    0b010_00000,
+    0b010_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    // '('
+    0b001_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    0b001_00000,
+    // ')'
+    0b100_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    0b100_00000,
+    // '*'
+    0b000_00000,
+    0b101_00000,
+    0b010_00000,
+    0b101_00000,
+    0b000_00000,
+    // '+'
+    0b000_00000,
+    0b010_00000,
+    0b111_00000,
+    0b010_00000,
+    0b000_00000,
+    // ','
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b010_00000,
+    0b100_00000,
+    // '-'
+    0b000_00000,
+    0b000_00000,
+    0b111_00000,
+    0b000_00000,
+    0b000_00000,
+    // '.'
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b010_00000,
+    // '/'
+    0b001_00000,
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    0b100_00000,
+    // '0'
+    0b111_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    // '1'
+    0b010_00000,
+    0b110_00000,
+    0b010_00000,
+    0b010_00000,
+    0b111_00000,
+    // '2'
+    0b110_00000,
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    0b111_00000,
+    // '3'
+    0b110_00000,
+    0b001_00000,
+    0b010_00000,
+    0b001_00000,
+    0b110_00000,
+    // '4'
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    0b001_00000,
+    0b001_00000,
+    // '5'
+    0b111_00000,
+    0b100_00000,
+    0b110_00000,
+    0b001_00000,
+    0b110_00000,
+    // '6'
+    0b011_00000,
+    0b100_00000,
+    0b111_00000,
+    0b101_00000,
+    0b111_00000,
+    // '7'
+    0b111_00000,
+    0b001_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    // '8'
+    0b111_00000,
+    0b101_00000,
+    0b111_00000,
+    0b101_00000,
+    0b111_00000,
+    // '9'
+    0b111_00000,
+    0b101_00000,
+    0b111_00000,
+    0b001_00000,
+    0b110_00000,
+    // ':'
+    0b000_00000,
+    0b010_00000,
+    0b000_00000,
+    0b010_00000,
+    0b000_00000,
+    // ';'
+    0b000_00000,
+    0b010_00000,
+    0b000_00000,
+    0b010_00000,
+    0b100_00000,
+    // '<'
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    0b010_00000,
+    0b001_00000,
+    // '='
+    0b000_00000,
+    0b111_00000,
+    0b000_00000,
+    0b111_00000,
+    0b000_00000,
+    // '>'
+    0b100_00000,
+    0b010_00000,
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    // '?'
+    0b110_00000,
+    0b001_00000,
+    0b010_00000,
+    0b000_00000,
+    0b010_00000,
+    // '@'
+    0b010_00000,
+    0b101_00000,
+    0b111_00000,
+    0b100_00000,
+    0b011_00000,
+    // 'A'
+    0b010_00000,
+    0b101_00000,
+    0b111_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'B'
+    0b110_00000,
+    0b101_00000,
+    0b110_00000,
+    0b101_00000,
+    0b110_00000,
+    // 'C'
+    0b011_00000,
+    0b100_00000,
+    0b100_00000,
+    0b100_00000,
+    0b011_00000,
+    // 'D'
+    0b110_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b110_00000,
+    // 'E'
+    0b111_00000,
+    0b100_00000,
+    0b110_00000,
+    0b100_00000,
+    0b111_00000,
+    // 'F'
+    0b111_00000,
+    0b100_00000,
+    0b110_00000,
+    0b100_00000,
+    0b100_00000,
+    // 'G'
+    0b011_00000,
+    0b100_00000,
+    0b101_00000,
+    0b101_00000,
+    0b011_00000,
+    // 'H'
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'I'
+    0b111_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    0b111_00000,
+    // 'J'
+    0b001_00000,
+    0b001_00000,
+    0b001_00000,
+    0b101_00000,
+    0b010_00000,
+    // 'K'
+    0b101_00000,
+    0b101_00000,
+    0b110_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'L'
+    0b100_00000,
+    0b100_00000,
+    0b100_00000,
+    0b100_00000,
+    0b111_00000,
+    // 'M'
+    0b101_00000,
+    0b111_00000,
+    0b111_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'N'
+    0b110_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'O'
+    0b010_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b010_00000,
+    // 'P'
+    0b110_00000,
+    0b101_00000,
+    0b110_00000,
+    0b100_00000,
+    0b100_00000,
+    // 'Q'
+    0b010_00000,
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    0b011_00000,
+    // 'R'
+    0b110_00000,
+    0b101_00000,
+    0b110_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'S'
+    0b011_00000,
+    0b100_00000,
+    0b010_00000,
+    0b001_00000,
+    0b110_00000,
+    // 'T'
+    0b111_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    // 'U'
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    // 'V'
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b101_00000,
+    0b010_00000,
+    // 'W'
+    0b101_00000,
+    0b101_00000,
+    0b111_00000,
+    0b111_00000,
+    0b101_00000,
+    // 'X'
+    0b101_00000,
+    0b101_00000,
+    0b010_00000,
+    0b101_00000,
+    0b101_00000,
+    // 'Y'
+    0b101_00000,
+    0b101_00000,
+    0b010_00000,
+    0b010_00000,
+    0b010_00000,
+    // 'Z'
+    0b111_00000,
+    0b001_00000,
+    0b010_00000,
+    0b100_00000,
+    0b111_00000,
+    // '['
+    0b110_00000,
+    0b100_00000,
+    0b100_00000,
+    0b100_00000,
+    0b110_00000,
+    // '\\'
+    0b100_00000,
+    0b100_00000,
+    0b010_00000,
+    0b001_00000,
+    0b001_00000,
+    // ']'
+    0b011_00000,
+    0b001_00000,
+    0b001_00000,
+    0b001_00000,
+    0b011_00000,
+    // '^'
+    0b010_00000,
+    0b101_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    // '_'
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b000_00000,
+    0b111_00000,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::text::TextDisplay;
+
+    fn draw(text: &str, x: i16, y: i16, font: &Font, direction: TextDirection) -> TextDisplay {
+        let mut display = TextDisplay::new();
+        draw_text(&mut display, font, text, x, y, direction);
+        display
+    }
+
+    #[test]
+    fn font_8x8_leaves_the_last_column_blank() {
+        for c in ' '..='~' {
+            let glyph = FONT_8X8.glyph(c).unwrap();
+            assert!(glyph.iter().all(|row| row & 1 == 0), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn proportional_characters_take_only_their_columns() {
+        let display = draw("!L", 0, 0, &FONT_3X5, TextDirection::Horizontal);
+        assert_eq!(
+            display.data[..6],
+            [
+                0b1010_0000,
+                0b1010_0000,
+                0b1010_0000,
+                0b0010_0000,
+                0b1011_1000,
+                0b0000_0000,
+            ]
+        );
+        assert_eq!(FONT_3X5.text_length("!L", TextDirection::Horizontal), 5);
+        assert_eq!(FONT_3X5.text_length("1 1", TextDirection::Horizontal), 10);
+    }
+
+    #[test]
+    fn monospaced_characters_keep_their_cell() {
+        let font = FONT_3X5.with_proportional(false);
+        let display = draw("!L", 0, 0, &font, TextDirection::Horizontal);
+        assert_eq!(display.data[0], 0b0100_1000);
+        assert_eq!(display.data[4], 0b0100_1110);
+        assert_eq!(font.text_length("!L", TextDirection::Horizontal), 7);
+        assert_eq!(
+            font.with_spacing(0)
+                .text_length("!L", TextDirection::Horizontal),
+            6
+        );
+    }
+
+    #[test]
+    fn vertical_text_stacks_upright_characters() {
+        let display = draw("LT", 1, 0, &FONT_3X5, TextDirection::Vertical);
+        assert_eq!(
+            display.data[..12],
+            [
+                0b0100_0000,
+                0b0100_0000,
+                0b0100_0000,
+                0b0100_0000,
+                0b0111_0000,
+                0b0000_0000,
+                0b0111_0000,
+                0b0010_0000,
+                0b0010_0000,
+                0b0010_0000,
+                0b0010_0000,
+                0b0000_0000,
+            ]
+        );
+        assert_eq!(FONT_3X5.text_length("LT", TextDirection::Vertical), 11);
+    }
+
+    #[test]
+    fn rotated_text_turns_characters_clockwise() {
+        let display = draw("LT", 0, 0, &FONT_3X5, TextDirection::Rotated);
+        assert_eq!(
+            display.data[..8],
+            [
+                0b1111_1000,
+                0b1000_0000,
+                0b1000_0000,
+                0b0000_0000,
+                0b0000_1000,
+                0b1111_1000,
+                0b0000_1000,
+                0b0000_0000,
+            ]
+        );
+        assert_eq!(FONT_3X5.text_length("LT", TextDirection::Rotated), 7);
+    }
+
+    #[test]
+    fn cuts_off_text_outside_of_the_display() {
+        let display = draw("L", -2, 0, &FONT_3X5, TextDirection::Horizontal);
+        assert_eq!(display.data[..5], [0, 0, 0, 0, 0b1000_0000]);
+
+        let display = draw("L", 0, -4, &FONT_3X5, TextDirection::Horizontal);
+        assert_eq!(display.data[..2], [0b1110_0000, 0]);
+
+        let display = draw("L", 7, 30, &FONT_3X5, TextDirection::Horizontal);
+        assert_eq!(display.data[28..], [0, 0, 0b0000_0001, 0b0000_0001]);
+
+        let display = draw("LT", -100, -100, &FONT_3X5, TextDirection::Rotated);
+        assert!(display.data.iter().all(|&row| row == 0));
+    }
+}
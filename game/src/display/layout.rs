@@ -0,0 +1,430 @@
+use std::fmt;
+
+/// Width and height of a single LED matrix module in pixels
+pub const MODULE_SIZE: u8 = 8;
+
+/// Largest number of modules in one direction, so sizes in pixels fit into a `u8`
+pub(super) const MAX_MODULES: u8 = u8::MAX / MODULE_SIZE;
+
+/// How a module is mounted, turned clockwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::Deg0,
+        Rotation::Deg90,
+        Rotation::Deg180,
+        Rotation::Deg270,
+    ];
+
+    pub const fn degrees(self) -> u16 {
+        match self {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 90,
+            Rotation::Deg180 => 180,
+            Rotation::Deg270 => 270,
+        }
+    }
+
+    pub fn from_degrees(degrees: u16) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|rotation| rotation.degrees() == degrees)
+    }
+}
+
+/// The order in which chained modules, or the LEDs of a strip, cover a grid, starting top left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChainOrder {
+    /// Left to right through each row, rows from top to bottom
+    #[default]
+    Rows,
+    /// Like [`ChainOrder::Rows`], but every second row runs right to left
+    SerpentineRows,
+    /// Top to bottom through each column, columns from left to right
+    Columns,
+    /// Like [`ChainOrder::Columns`], but every second column runs bottom to top
+    SerpentineColumns,
+}
+
+impl ChainOrder {
+    pub const ALL: [ChainOrder; 4] = [
+        ChainOrder::Rows,
+        ChainOrder::SerpentineRows,
+        ChainOrder::Columns,
+        ChainOrder::SerpentineColumns,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            ChainOrder::Rows => "rows",
+            ChainOrder::SerpentineRows => "serpentine_rows",
+            ChainOrder::Columns => "columns",
+            ChainOrder::SerpentineColumns => "serpentine_columns",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.name() == name)
+    }
+
+    /// Position in the chain of the cell at `column` and `row` of a `columns` by `rows` grid
+    pub fn index(self, column: usize, row: usize, columns: usize, rows: usize) -> usize {
+        match self {
+            ChainOrder::Rows => row * columns + column,
+            ChainOrder::SerpentineRows if row % 2 == 1 => row * columns + (columns - 1 - column),
+            ChainOrder::SerpentineRows => row * columns + column,
+            ChainOrder::Columns => column * rows + row,
+            ChainOrder::SerpentineColumns if column % 2 == 1 => column * rows + (rows - 1 - row),
+            ChainOrder::SerpentineColumns => column * rows + row,
+        }
+    }
+}
+
+/// How a single module is mounted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    /// Flipped left to right, applied before the rotation
+    pub mirrored: bool,
+}
+
+/// Arrangement of chained LED matrix modules forming one display.
+///
+/// Modules are numbered by their position in the chain, starting with the one
+/// closest to the controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    columns: u8,
+    rows: u8,
+    order: ChainOrder,
+    orientations: Vec<Orientation>,
+}
+
+impl Default for Layout {
+    // Our prototype, four modules stacked vertically
+    fn default() -> Self {
+        Self::new(1, 4)
+    }
+}
+
+impl Layout {
+    /// A grid of unrotated modules chained row by row.
+    /// The grid is limited to 31 modules in each direction.
+    pub fn new(columns: u8, rows: u8) -> Self {
+        let columns = columns.clamp(1, MAX_MODULES);
+        let rows = rows.clamp(1, MAX_MODULES);
+        Self {
+            columns,
+            rows,
+            order: ChainOrder::default(),
+            orientations: vec![Orientation::default(); columns as usize * rows as usize],
+        }
+    }
+
+    pub fn with_order(mut self, order: ChainOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Rotates all modules
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        for orientation in &mut self.orientations {
+            orientation.rotation = rotation;
+        }
+        self
+    }
+
+    /// Mirrors all modules
+    pub fn with_mirrored(mut self, mirrored: bool) -> Self {
+        for orientation in &mut self.orientations {
+            orientation.mirrored = mirrored;
+        }
+        self
+    }
+
+    /// Rotates the module at the given chain position, other positions are ignored
+    pub fn with_module_rotation(mut self, module: usize, rotation: Rotation) -> Self {
+        if let Some(orientation) = self.orientations.get_mut(module) {
+            orientation.rotation = rotation;
+        }
+        self
+    }
+
+    /// Mirrors the module at the given chain position, other positions are ignored
+    pub fn with_module_mirrored(mut self, module: usize, mirrored: bool) -> Self {
+        if let Some(orientation) = self.orientations.get_mut(module) {
+            orientation.mirrored = mirrored;
+        }
+        self
+    }
+
+    /// Number of chained modules
+    pub fn modules(&self) -> usize {
+        self.orientations.len()
+    }
+
+    /// Width and height in pixels
+    pub fn size(&self) -> (u8, u8) {
+        (self.columns * MODULE_SIZE, self.rows * MODULE_SIZE)
+    }
+
+    pub fn order(&self) -> ChainOrder {
+        self.order
+    }
+
+    pub fn orientation(&self, module: usize) -> Option<Orientation> {
+        self.orientations.get(module).copied()
+    }
+
+    /// Finds the module showing the given pixel, along with the pixel's
+    /// coordinates as seen by the unrotated module
+    pub fn locate(&self, x: u8, y: u8) -> Option<(usize, u8, u8)> {
+        let (width, height) = self.size();
+        if x >= width || y >= height {
+            return None;
+        }
+
+        let (column, row) = (x / MODULE_SIZE, y / MODULE_SIZE);
+        let module = self.order.index(
+            column as usize,
+            row as usize,
+            self.columns as usize,
+            self.rows as usize,
+        );
+
+        let last = MODULE_SIZE - 1;
+        let orientation = self.orientations[module];
+        let mut local_x = x % MODULE_SIZE;
+        let local_y = y % MODULE_SIZE;
+        if orientation.mirrored {
+            local_x = last - local_x;
+        }
+
+        let (local_x, local_y) = match orientation.rotation {
+            Rotation::Deg0 => (local_x, local_y),
+            // Swap x and y, then flip y
+            Rotation::Deg90 => (local_y, last - local_x),
+            // Flip both x and y
+            Rotation::Deg180 => (last - local_x, last - local_y),
+            // Swap x and y, then flip x
+            Rotation::Deg270 => (last - local_y, local_x),
+        };
+
+        Some((module, local_x, local_y))
+    }
+
+    pub fn serialize(&self) -> String {
+        let rotations = self
+            .orientations
+            .iter()
+            .map(|orientation| orientation.rotation.degrees().to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mirrored = self
+            .orientations
+            .iter()
+            .map(|orientation| orientation.mirrored.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "columns={}\n\
+             rows={}\n\
+             order={}\n\
+             rotation={}\n\
+             mirrored={}\n",
+            self.columns,
+            self.rows,
+            self.order.name(),
+            rotations,
+            mirrored,
+        )
+    }
+
+    /// Parses `key=value` lines as written by [`Layout::serialize`].
+    ///
+    /// `rotation` and `mirrored` take either one value per module in chain order
+    /// or a single value for all modules. Missing keys keep the value of the
+    /// default layout. Empty lines and lines starting with `#` are ignored.
+    pub fn deserialize(string: &str) -> Result<Self, ParseLayoutError> {
+        let entries = string
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_once('=')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .ok_or_else(|| ParseLayoutError::MissingSeparator(line.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let default = Self::default();
+        let mut columns = default.columns;
+        let mut rows = default.rows;
+        let mut order = default.order;
+        let mut rotations = Vec::new();
+        let mut mirrored = Vec::new();
+
+        for (key, value) in entries {
+            let invalid = || ParseLayoutError::InvalidValue(key.to_string());
+            let modules = || {
+                value
+                    .parse()
+                    .ok()
+                    .filter(|modules| (1..=MAX_MODULES).contains(modules))
+                    .ok_or_else(invalid)
+            };
+
+            match key {
+                "columns" => columns = modules()?,
+                "rows" => rows = modules()?,
+                "order" => order = ChainOrder::from_name(value).ok_or_else(invalid)?,
+                "rotation" => {
+                    rotations = value
+                        .split(',')
+                        .map(|degrees| degrees.trim().parse().ok().and_then(Rotation::from_degrees))
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                "mirrored" => {
+                    mirrored = value
+                        .split(',')
+                        .map(|mirrored| mirrored.trim().parse().ok())
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                _ => return Err(ParseLayoutError::UnknownKey(key.to_string())),
+            }
+        }
+
+        let mut layout = Self::new(columns, rows).with_order(order);
+        let modules = layout.modules();
+        match rotations[..] {
+            [] => {}
+            [rotation] => layout = layout.with_rotation(rotation),
+            _ if rotations.len() == modules => {
+                for (module, rotation) in rotations.into_iter().enumerate() {
+                    layout = layout.with_module_rotation(module, rotation);
+                }
+            }
+            _ => return Err(ParseLayoutError::InvalidValue("rotation".to_string())),
+        }
+        match mirrored[..] {
+            [] => {}
+            [mirrored] => layout = layout.with_mirrored(mirrored),
+            _ if mirrored.len() == modules => {
+                for (module, mirrored) in mirrored.into_iter().enumerate() {
+                    layout = layout.with_module_mirrored(module, mirrored);
+                }
+            }
+            _ => return Err(ParseLayoutError::InvalidValue("mirrored".to_string())),
+        }
+
+        Ok(layout)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLayoutError {
+    /// A line without `=`
+    MissingSeparator(String),
+    /// A key that is not part of the layout
+    UnknownKey(String),
+    /// The value for the given key could not be parsed
+    InvalidValue(String),
+}
+
+impl fmt::Display for ParseLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLayoutError::MissingSeparator(line) => write!(f, "missing '=' in line '{line}'"),
+            ParseLayoutError::UnknownKey(key) => write!(f, "unknown key '{key}'"),
+            ParseLayoutError::InvalidValue(key) => write!(f, "invalid value for '{key}'"),
+        }
+    }
+}
+
+impl std::error::Error for ParseLayoutError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serpentine_chains_reverse_every_second_row() {
+        let layout = Layout::new(3, 2).with_order(ChainOrder::SerpentineRows);
+        assert_eq!(layout.locate(0, 0), Some((0, 0, 0)));
+        assert_eq!(layout.locate(23, 0), Some((2, 7, 0)));
+        assert_eq!(layout.locate(23, 8), Some((3, 7, 0)));
+        assert_eq!(layout.locate(0, 15), Some((5, 0, 7)));
+        assert_eq!(layout.locate(24, 0), None);
+        assert_eq!(layout.locate(0, 16), None);
+    }
+
+    #[test]
+    fn rotates_and_mirrors_single_modules() {
+        let layout = Layout::new(1, 2)
+            .with_module_rotation(0, Rotation::Deg90)
+            .with_module_mirrored(1, true);
+        // The top left pixel of each module, and the one right of it
+        assert_eq!(layout.locate(0, 0), Some((0, 0, 7)));
+        assert_eq!(layout.locate(1, 0), Some((0, 0, 6)));
+        assert_eq!(layout.locate(0, 8), Some((1, 7, 0)));
+        assert_eq!(layout.locate(1, 8), Some((1, 6, 0)));
+
+        for (rotation, expected) in [
+            (Rotation::Deg0, (1, 0)),
+            (Rotation::Deg90, (0, 6)),
+            (Rotation::Deg180, (6, 7)),
+            (Rotation::Deg270, (7, 1)),
+        ] {
+            let (_, x, y) = Layout::new(1, 1)
+                .with_rotation(rotation)
+                .locate(1, 0)
+                .unwrap();
+            assert_eq!((x, y), expected, "{rotation:?}");
+        }
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let layout = Layout::new(2, 3)
+            .with_order(ChainOrder::SerpentineColumns)
+            .with_rotation(Rotation::Deg180)
+            .with_module_rotation(4, Rotation::Deg270)
+            .with_module_mirrored(1, true);
+        assert_eq!(Layout::deserialize(&layout.serialize()), Ok(layout));
+
+        let shared = Layout::deserialize("columns=2\nrotation=90\nmirrored=true").unwrap();
+        assert_eq!(shared.modules(), 8);
+        assert!((0..8).all(|module| {
+            shared.orientation(module)
+                == Some(Orientation {
+                    rotation: Rotation::Deg90,
+                    mirrored: true,
+                })
+        }));
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let invalid = |key: &str| Err(ParseLayoutError::InvalidValue(key.to_string()));
+        assert_eq!(Layout::deserialize("rows=0"), invalid("rows"));
+        assert_eq!(Layout::deserialize("columns=32"), invalid("columns"));
+        assert_eq!(Layout::deserialize("rotation=45"), invalid("rotation"));
+        // Two values for four modules
+        assert_eq!(Layout::deserialize("rotation=0,90"), invalid("rotation"));
+        assert_eq!(
+            Layout::deserialize("size=4"),
+            Err(ParseLayoutError::UnknownKey("size".to_string()))
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use embedded_hal_async::spi::SpiDevice;
+
+use super::{Max72xxChain, op};
+use crate::display::layout::{Layout, MAX_MODULES};
+
+/// Like [`super::Max72xx`], but the frames are pushed with `embedded-hal-async`,
+/// so the game logic can keep running while the SPI peripheral transfers them.
+///
+/// Drawing happens on [`Max72xxAsync::chain_mut`], which implements
+/// [`crate::display::Display`].
+pub struct Max72xxAsync<SPI> {
+    spi: SPI,
+    chain: Max72xxChain,
+}
+
+impl<E, SPI: SpiDevice<Error = E>> Max72xxAsync<SPI> {
+    /// A single column of `displays` unrotated modules.
+    ///
+    /// # Panics
+    ///
+    /// With more than 31 modules, which would not fit into the height of a display
+    pub fn new(spi: SPI, displays: usize) -> Self {
+        let rows = u8::try_from(displays)
+            .ok()
+            .filter(|&rows| rows <= MAX_MODULES)
+            .expect("at most 31 modules fit into a column");
+        Self::with_layout(spi, Layout::new(1, rows))
+    }
+
+    pub fn with_layout(spi: SPI, layout: Layout) -> Self {
+        Self {
+            spi,
+            chain: Max72xxChain::new(layout),
+        }
+    }
+
+    pub fn chain(&self) -> &Max72xxChain {
+        &self.chain
+    }
+
+    /// The frame buffer to draw on, sent with the next transfer
+    pub fn chain_mut(&mut self) -> &mut Max72xxChain {
+        &mut self.chain
+    }
+
+    /// Writes all control registers, the digits are sent again with the next transfer
+    pub async fn reset(&mut self) -> Result<(), E> {
+        self.chain.queue_reset();
+        self.send().await
+    }
+
+    /// See [`super::Max72xx::set_reinit_interval`]
+    pub fn set_reinit_interval(&mut self, frames: Option<u32>) {
+        self.chain.set_reinit_interval(frames);
+    }
+
+    pub async fn set_shutdown(&mut self, value: bool) -> Result<(), E> {
+        self.chain.set_shutdown(None, value);
+        self.send_register(op::SHUTDOWN, None).await
+    }
+
+    /// Intensity from 0 to 15
+    pub async fn set_intensity(&mut self, intensity: u8) -> Result<(), E> {
+        self.chain.set_intensity(None, intensity);
+        self.send_register(op::INTENSITY, None).await
+    }
+
+    /// Lights all LEDs of every module, regardless of the bitmap
+    pub async fn set_display_test(&mut self, value: bool) -> Result<(), E> {
+        self.chain.set_display_test(None, value);
+        self.send_register(op::DISPLAYTEST, None).await
+    }
+
+    /// Blanks or shows the module at the given chain position, other positions are ignored
+    pub async fn set_module_shutdown(&mut self, module: usize, value: bool) -> Result<(), E> {
+        if !self.chain.set_shutdown(Some(module), value) {
+            return Ok(());
+        }
+        self.send_register(op::SHUTDOWN, Some(module)).await
+    }
+
+    /// Intensity from 0 to 15 of the module at the given chain position,
+    /// other positions are ignored
+    pub async fn set_module_intensity(&mut self, module: usize, intensity: u8) -> Result<(), E> {
+        if !self.chain.set_intensity(Some(module), intensity) {
+            return Ok(());
+        }
+        self.send_register(op::INTENSITY, Some(module)).await
+    }
+
+    /// Lights all LEDs of the module at the given chain position, e.g. to find a bad module.
+    /// Other positions are ignored.
+    pub async fn set_module_display_test(&mut self, module: usize, value: bool) -> Result<(), E> {
+        if !self.chain.set_display_test(Some(module), value) {
+            return Ok(());
+        }
+        self.send_register(op::DISPLAYTEST, Some(module)).await
+    }
+
+    /// Sends the rows that changed since the last transfer.
+    ///
+    /// After a failed write the chain is re-initialised before the next transfer.
+    pub async fn transfer_bitmap(&mut self) -> Result<(), E> {
+        self.chain.queue_frame();
+        self.send().await
+    }
+
+    /// Sends all rows, whether they changed or not
+    pub async fn force_full_refresh(&mut self) -> Result<(), E> {
+        self.chain.invalidate();
+        self.transfer_bitmap().await
+    }
+
+    async fn send_register(&mut self, opcode: u8, module: Option<usize>) -> Result<(), E> {
+        self.chain.queue_register(opcode, module);
+        self.send().await
+    }
+
+    /// Sends everything the chain queued, one transaction per write
+    async fn send(&mut self) -> Result<(), E> {
+        let mut result = Ok(());
+        for buffer in self.chain.queued() {
+            result = self.spi.write(buffer).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        self.chain.finish(result.is_ok());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::Display;
+    use crate::display::testing::{SpiRecorder, block_on};
+
+    #[test]
+    fn sends_like_the_blocking_driver() {
+        let mut display = Max72xxAsync::new(SpiRecorder::default(), 2);
+        block_on(display.reset()).unwrap();
+        assert_eq!(display.spi.writes.len(), 5);
+        assert!(display.spi.writes.iter().all(|write| write.len() == 4));
+
+        block_on(display.transfer_bitmap()).unwrap();
+        assert_eq!(display.spi.writes.len(), 5 + 8);
+
+        display.spi.writes.clear();
+        display.chain_mut().set_pixel(3, 10, true);
+        block_on(display.transfer_bitmap()).unwrap();
+        assert_eq!(
+            display.spi.writes,
+            [vec![op::DIGIT0 + 3, 0b100, op::NOOP, 0]]
+        );
+    }
+
+    #[test]
+    fn failed_write_resets_the_chain() {
+        let mut display = Max72xxAsync::new(SpiRecorder::default(), 1);
+        display.spi.fail = true;
+        assert!(block_on(display.transfer_bitmap()).is_err());
+
+        block_on(display.transfer_bitmap()).unwrap();
+        assert_eq!(display.spi.writes.len(), 5 + 8);
+        assert_eq!(display.spi.writes[0], [op::DISPLAYTEST, 0]);
+    }
+}
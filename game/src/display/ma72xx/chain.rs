@@ -0,0 +1,406 @@
+use super::{DecodeMode, op};
+use crate::display::layout::{Layout, MODULE_SIZE};
+
+/// Registers written by a reset and the periodic re-initialisation
+pub(super) const CONTROL_REGISTERS: [u8; 5] = [
+    op::DISPLAYTEST,
+    op::SCANLIMIT,
+    op::DECODEMODE,
+    op::SHUTDOWN,
+    op::INTENSITY,
+];
+
+/// Control register settings of a single module
+#[derive(Debug, Clone, Copy)]
+struct ModuleSettings {
+    shutdown: bool,
+    intensity: u8,
+    display_test: bool,
+}
+
+impl Default for ModuleSettings {
+    fn default() -> Self {
+        Self {
+            shutdown: false,
+            // A medium brightness
+            intensity: 2,
+            display_test: false,
+        }
+    }
+}
+
+/// Frame buffer and register settings of a chain of MAX72xx modules,
+/// shared by the blocking [`super::Max72xx`] and the async driver.
+///
+/// The drivers queue the SPI writes for an operation in one reused buffer,
+/// send them, and report back whether all of them went through.
+pub struct Max72xxChain {
+    bitmap: Vec<u8>,
+    /// The bitmap as it was last sent to the modules
+    sent: Vec<u8>,
+    /// Whether the next transfer has to send every row, e.g. because the
+    /// content of the modules is unknown
+    refresh_all: bool,
+    /// Set when a write failed, the next transfer re-initialises the chain first
+    needs_reset: bool,
+    /// Control register settings per module, in chain order
+    modules: Vec<ModuleSettings>,
+    /// Intensity changed through [`Display::set_brightness`], sent with the next transfer
+    ///
+    /// [`Display::set_brightness`]: crate::display::Display::set_brightness
+    intensity_changed: bool,
+    /// Transfers between two re-initialisation steps, `None` disables them
+    reinit_interval: Option<u32>,
+    /// Transfers since the last re-initialisation step
+    reinit_frames: u32,
+    /// The next step of the periodic re-initialisation
+    reinit_step: usize,
+    /// Queued writes of `displays * 2` bytes each, kept between transfers
+    /// so that queueing usually does not allocate
+    queue: Vec<u8>,
+    /// Whether the queue resets the chain
+    queued_reset: bool,
+    /// Whether the queue sends the bitmap
+    queued_frame: bool,
+    displays: usize,
+    layout: Layout,
+}
+
+impl Max72xxChain {
+    pub(super) fn new(layout: Layout) -> Self {
+        let displays = layout.modules();
+        // Enough for a reset, a re-initialisation step, the intensity and all rows
+        let writes = CONTROL_REGISTERS.len() + 2 + 8;
+        Self {
+            bitmap: vec![0x00; displays * 8],
+            sent: vec![0x00; displays * 8],
+            refresh_all: true,
+            needs_reset: false,
+            modules: vec![ModuleSettings::default(); displays],
+            intensity_changed: false,
+            reinit_interval: None,
+            reinit_frames: 0,
+            reinit_step: 0,
+            queue: Vec::with_capacity(writes * displays * 2),
+            queued_reset: false,
+            queued_frame: false,
+            displays,
+            layout,
+        }
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Whether the next transfer sends any digits
+    pub fn is_dirty(&self) -> bool {
+        self.refresh_all || (0u8..8).any(|row| self.row_changed(row))
+    }
+
+    /// Makes the next transfer send all rows, whether they changed or not
+    pub fn invalidate(&mut self) {
+        self.refresh_all = true;
+    }
+
+    pub(super) fn set_reinit_interval(&mut self, frames: Option<u32>) {
+        self.reinit_interval = frames;
+        self.reinit_frames = 0;
+    }
+
+    pub(super) fn set_shutdown(&mut self, module: Option<usize>, value: bool) -> bool {
+        self.update_modules(module, |settings| settings.shutdown = value)
+    }
+
+    pub(super) fn set_intensity(&mut self, module: Option<usize>, intensity: u8) -> bool {
+        self.update_modules(module, |settings| settings.intensity = intensity.min(0x0f))
+    }
+
+    pub(super) fn set_display_test(&mut self, module: Option<usize>, value: bool) -> bool {
+        self.update_modules(module, |settings| settings.display_test = value)
+    }
+
+    /// Changes the settings of one module or of all of them with `None`,
+    /// returns `false` when there is no such module
+    fn update_modules(
+        &mut self,
+        module: Option<usize>,
+        update: impl Fn(&mut ModuleSettings),
+    ) -> bool {
+        match module {
+            Some(module) => match self.modules.get_mut(module) {
+                Some(settings) => update(settings),
+                None => return false,
+            },
+            None => self.modules.iter_mut().for_each(update),
+        }
+        true
+    }
+
+    /// Queues writing all control registers, the digits are sent again with the next transfer
+    pub(super) fn queue_reset(&mut self) {
+        // The digit registers may hold anything after power up
+        self.refresh_all = true;
+        self.queued_reset = true;
+
+        for opcode in CONTROL_REGISTERS {
+            self.queue_register(opcode, None);
+        }
+    }
+
+    /// Queues the rows that changed since the last transfer.
+    ///
+    /// After a failed write the chain is re-initialised before the next transfer.
+    pub(super) fn queue_frame(&mut self) {
+        if self.needs_reset {
+            self.queue_reset();
+        }
+        if std::mem::take(&mut self.intensity_changed) {
+            self.queue_register(op::INTENSITY, None);
+        }
+        self.queue_reinit_step();
+
+        for row in 0u8..8 {
+            if self.refresh_all || self.row_changed(row) {
+                self.queue_row(row);
+            }
+        }
+        self.queued_frame = true;
+    }
+
+    /// Queues writing a control register of one module or of all of them with `None`.
+    /// Every module gets its own value, modules that are left out get a NOOP.
+    pub(super) fn queue_register(&mut self, opcode: u8, module: Option<usize>) {
+        for display in (0..self.displays).rev() {
+            if module.is_none_or(|module| module == display) {
+                let data = self.register_value(opcode, display);
+                self.queue.extend_from_slice(&[opcode, data]);
+            } else {
+                self.queue.extend_from_slice(&[op::NOOP, 0x00]);
+            }
+        }
+    }
+
+    /// The queued writes, each one has to be sent as a separate SPI transaction
+    pub(super) fn queued(&self) -> std::slice::Chunks<'_, u8> {
+        self.queue.chunks(self.displays * 2)
+    }
+
+    /// Empties the queue, `success` tells whether all queued writes were sent
+    pub(super) fn finish(&mut self, success: bool) {
+        if !success {
+            // A failed write leaves the chain in an unknown state
+            self.needs_reset = true;
+            self.refresh_all = true;
+        } else {
+            if self.queued_reset {
+                self.needs_reset = false;
+            }
+            if self.queued_frame {
+                self.sent.copy_from_slice(&self.bitmap);
+                self.refresh_all = false;
+            }
+        }
+
+        self.queue.clear();
+        self.queued_reset = false;
+        self.queued_frame = false;
+    }
+
+    /// The value a control register of the given module should hold
+    fn register_value(&self, opcode: u8, module: usize) -> u8 {
+        let settings = &self.modules[module];
+        match opcode {
+            op::DISPLAYTEST => settings.display_test as u8,
+            // We need the multiplexer to scan all segments
+            op::SCANLIMIT => 7,
+            // We don't want the multiplexer to decode segments for us
+            op::DECODEMODE => DecodeMode::DecodeNo as u8,
+            op::SHUTDOWN => !settings.shutdown as u8,
+            op::INTENSITY => settings.intensity,
+            _ => unreachable!("not a control register"),
+        }
+    }
+
+    /// Queues the next step of the periodic re-initialisation once it is due
+    fn queue_reinit_step(&mut self) {
+        let Some(interval) = self.reinit_interval else {
+            return;
+        };
+        self.reinit_frames += 1;
+        if self.reinit_frames < interval {
+            return;
+        }
+        self.reinit_frames = 0;
+
+        let step = self.reinit_step;
+        self.reinit_step = (step + 1) % (CONTROL_REGISTERS.len() + 1);
+        match CONTROL_REGISTERS.get(step) {
+            Some(&opcode) => self.queue_register(opcode, None),
+            // The last step sends all digits with this transfer
+            None => self.refresh_all = true,
+        }
+    }
+
+    fn row_changed(&self, row: u8) -> bool {
+        (0..self.displays).any(|display| {
+            let index = display * 8 + row as usize;
+            self.bitmap[index] != self.sent[index]
+        })
+    }
+
+    /// Queues one row for the modules where it changed, the others get a NOOP
+    fn queue_row(&mut self, row: u8) {
+        assert!(row < 8);
+
+        let opcode = op::DIGIT0 + row;
+
+        for display in (0..self.displays).rev() {
+            let index = display * 8 + row as usize;
+            let data = self.bitmap[index];
+            if self.refresh_all || data != self.sent[index] {
+                self.queue.extend_from_slice(&[opcode, data]);
+            } else {
+                self.queue.extend_from_slice(&[op::NOOP, 0x00]);
+            }
+        }
+    }
+
+    /// Position of a pixel in the bitmap as index and bit mask
+    fn locate(&self, x: u8, y: u8) -> Option<(usize, u8)> {
+        let (display, local_x, local_y) = self.layout.locate(x, y)?;
+
+        // Each byte of a display is one digit register holding a column of 8 pixels
+        let index = display * MODULE_SIZE as usize + local_x as usize;
+        Some((index, 1 << local_y))
+    }
+}
+
+/// Drawing without sending anything, the drivers send the frame
+impl crate::display::Display for Max72xxChain {
+    type Error = std::convert::Infallible;
+
+    fn size(&self) -> (u8, u8) {
+        self.layout.size()
+    }
+
+    fn fill(&mut self, value: bool) {
+        let line = if value { 0xff } else { 0x00 };
+        self.bitmap.fill(line);
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        let Some((index, mask)) = self.locate(x, y) else {
+            return;
+        };
+        let line = &mut self.bitmap[index];
+        if value {
+            *line |= mask;
+        } else {
+            *line &= !mask;
+        }
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        self.locate(x, y)
+            .is_some_and(|(index, mask)| self.bitmap[index] & mask != 0)
+    }
+
+    /// Nothing to send here, the frame goes out with the driver's next transfer
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// The 16 intensity steps of the chip, sent with the driver's next transfer
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.set_intensity(None, brightness >> 4);
+        self.intensity_changed = true;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Max72xxChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.bitmap {
+            writeln!(f, "{:08b}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::Display;
+
+    /// A column of the given number of modules with nothing queued
+    fn chain(modules: u8) -> Max72xxChain {
+        let mut chain = Max72xxChain::new(Layout::new(1, modules));
+        chain.queue_frame();
+        chain.finish(true);
+        chain
+    }
+
+    fn queued(chain: &Max72xxChain) -> Vec<&[u8]> {
+        chain.queued().collect()
+    }
+
+    #[test]
+    fn single_module_gets_every_write() {
+        let mut chain = chain(1);
+        chain.set_intensity(Some(0), 5);
+        chain.queue_register(op::INTENSITY, Some(0));
+        chain.queue_register(op::SHUTDOWN, None);
+        assert_eq!(
+            queued(&chain),
+            [[op::INTENSITY, 5].as_slice(), &[op::SHUTDOWN, 1]]
+        );
+    }
+
+    #[test]
+    fn addresses_one_module_of_a_chain() {
+        let mut chain = chain(4);
+        chain.set_intensity(Some(1), 9);
+        chain.queue_register(op::INTENSITY, Some(1));
+        // The first pair is shifted through to the module furthest from the controller
+        assert_eq!(
+            queued(&chain),
+            [[op::NOOP, 0, op::NOOP, 0, op::INTENSITY, 9, op::NOOP, 0].as_slice()]
+        );
+    }
+
+    #[test]
+    fn addresses_every_module_of_a_chain() {
+        let mut chain = chain(4);
+        chain.set_intensity(Some(3), 9);
+        chain.queue_register(op::INTENSITY, None);
+        assert_eq!(
+            queued(&chain),
+            [[
+                op::INTENSITY,
+                9,
+                op::INTENSITY,
+                2,
+                op::INTENSITY,
+                2,
+                op::INTENSITY,
+                2
+            ]
+            .as_slice()]
+        );
+    }
+
+    #[test]
+    fn rows_skip_unchanged_modules() {
+        let mut chain = chain(4);
+        // Column 6 of the module closest to the controller and of the last one
+        chain.set_pixel(6, 0, true);
+        chain.set_pixel(6, 31, true);
+        chain.queue_frame();
+        let row = op::DIGIT0 + 6;
+        assert_eq!(
+            queued(&chain),
+            [[row, 0x80, op::NOOP, 0, op::NOOP, 0, row, 0x01].as_slice()]
+        );
+    }
+}
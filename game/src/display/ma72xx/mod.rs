@@ -0,0 +1,297 @@
+use embedded_hal::spi::SpiDevice;
+
+use super::layout::{Layout, MAX_MODULES};
+
+mod chain;
+pub use chain::Max72xxChain;
+
+#[cfg(feature = "async")]
+mod async_driver;
+#[cfg(feature = "async")]
+pub use async_driver::Max72xxAsync;
+
+pub struct Max72xx<SPI> {
+    spi: SPI,
+    chain: Max72xxChain,
+}
+
+#[allow(unused)]
+pub(super) mod op {
+    pub const NOOP: u8 = 0x0;
+    pub const DIGIT0: u8 = 0x1;
+    pub const DIGIT7: u8 = 0x8;
+    pub const DECODEMODE: u8 = 0x9;
+    pub const INTENSITY: u8 = 0xa;
+    pub const SCANLIMIT: u8 = 0xb;
+    pub const SHUTDOWN: u8 = 0xc;
+    pub const DISPLAYTEST: u8 = 0xf;
+}
+
+#[allow(unused)]
+#[repr(u8)]
+pub(super) enum DecodeMode {
+    DecodeNo = 0x00,
+    Decode0 = 0x01,
+    Decode3_0 = 0x0f,
+    Decode7_0 = 0xff,
+}
+
+impl<E, SPI: SpiDevice<Error = E>> Max72xx<SPI> {
+    /// A single column of `displays` unrotated modules.
+    ///
+    /// # Panics
+    ///
+    /// With more than 31 modules, which would not fit into the height of a display
+    pub fn new(spi: SPI, displays: usize) -> Self {
+        let rows = u8::try_from(displays)
+            .ok()
+            .filter(|&rows| rows <= MAX_MODULES)
+            .expect("at most 31 modules fit into a column");
+        Self::with_layout(spi, Layout::new(1, rows))
+    }
+
+    pub fn with_layout(spi: SPI, layout: Layout) -> Self {
+        Self {
+            spi,
+            chain: Max72xxChain::new(layout),
+        }
+    }
+
+    pub fn layout(&self) -> &Layout {
+        self.chain.layout()
+    }
+
+    /// Writes all control registers, the digits are sent again with the next transfer
+    pub fn reset(&mut self) -> Result<(), E> {
+        self.chain.queue_reset();
+        self.send()
+    }
+
+    /// Rewrites one control register every `frames` transfers, and all digits once all
+    /// registers are done, so a chain that glitched after ESD or a power dip
+    /// recovers on its own. `None` turns this off.
+    pub fn set_reinit_interval(&mut self, frames: Option<u32>) {
+        self.chain.set_reinit_interval(frames);
+    }
+
+    pub fn set_shutdown(&mut self, value: bool) -> Result<(), E> {
+        self.chain.set_shutdown(None, value);
+        self.send_register(op::SHUTDOWN, None)
+    }
+
+    /// Intensity from 0 to 15
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<(), E> {
+        self.chain.set_intensity(None, intensity);
+        self.send_register(op::INTENSITY, None)
+    }
+
+    /// Lights all LEDs of every module, regardless of the bitmap
+    pub fn set_display_test(&mut self, value: bool) -> Result<(), E> {
+        self.chain.set_display_test(None, value);
+        self.send_register(op::DISPLAYTEST, None)
+    }
+
+    /// Blanks or shows the module at the given chain position, other positions are ignored
+    pub fn set_module_shutdown(&mut self, module: usize, value: bool) -> Result<(), E> {
+        if !self.chain.set_shutdown(Some(module), value) {
+            return Ok(());
+        }
+        self.send_register(op::SHUTDOWN, Some(module))
+    }
+
+    /// Intensity from 0 to 15 of the module at the given chain position,
+    /// other positions are ignored
+    pub fn set_module_intensity(&mut self, module: usize, intensity: u8) -> Result<(), E> {
+        if !self.chain.set_intensity(Some(module), intensity) {
+            return Ok(());
+        }
+        self.send_register(op::INTENSITY, Some(module))
+    }
+
+    /// Lights all LEDs of the module at the given chain position, e.g. to find a bad module.
+    /// Other positions are ignored.
+    pub fn set_module_display_test(&mut self, module: usize, value: bool) -> Result<(), E> {
+        if !self.chain.set_display_test(Some(module), value) {
+            return Ok(());
+        }
+        self.send_register(op::DISPLAYTEST, Some(module))
+    }
+
+    /// Sends the rows that changed since the last transfer.
+    ///
+    /// After a failed write the chain is re-initialised before the next transfer.
+    pub fn transfer_bitmap(&mut self) -> Result<(), E> {
+        self.chain.queue_frame();
+        self.send()
+    }
+
+    /// Whether the next transfer sends any digits
+    pub fn is_dirty(&self) -> bool {
+        self.chain.is_dirty()
+    }
+
+    /// Sends all rows, whether they changed or not
+    pub fn force_full_refresh(&mut self) -> Result<(), E> {
+        self.chain.invalidate();
+        self.transfer_bitmap()
+    }
+
+    fn send_register(&mut self, opcode: u8, module: Option<usize>) -> Result<(), E> {
+        self.chain.queue_register(opcode, module);
+        self.send()
+    }
+
+    /// Sends everything the chain queued, one transaction per write
+    fn send(&mut self) -> Result<(), E> {
+        let result = self
+            .chain
+            .queued()
+            .try_for_each(|buffer| self.spi.write(buffer));
+        self.chain.finish(result.is_ok());
+        result
+    }
+}
+
+impl<SPI: SpiDevice> super::Display for Max72xx<SPI> {
+    type Error = SPI::Error;
+
+    fn size(&self) -> (u8, u8) {
+        self.chain.size()
+    }
+
+    fn fill(&mut self, value: bool) {
+        self.chain.fill(value);
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        self.chain.set_pixel(x, y, value);
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        self.chain.get_pixel(x, y)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.transfer_bitmap()
+    }
+
+    /// The 16 intensity steps of the chip, even the lowest one is still lit
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.set_intensity(brightness >> 4)
+    }
+}
+
+impl<SPI> std::fmt::Display for Max72xx<SPI> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.chain.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::spi::ErrorKind;
+
+    use super::*;
+    use crate::display::Display;
+    use crate::display::testing::SpiRecorder;
+
+    /// Two stacked modules that were reset and sent a frame, with nothing recorded yet
+    fn sent() -> Max72xx<SpiRecorder> {
+        let mut display = Max72xx::new(SpiRecorder::default(), 2);
+        display.reset().unwrap();
+        display.transfer_bitmap().unwrap();
+        display.spi.writes.clear();
+        display
+    }
+
+    /// The opcode each write sends to the given module, counted from the controller
+    fn opcodes(writes: &[Vec<u8>], module: usize) -> Vec<u8> {
+        writes
+            .iter()
+            .map(|write| write[write.len() - 2 * (module + 1)])
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_frame_sends_nothing() {
+        let mut display = sent();
+        assert!(!display.is_dirty());
+        display.transfer_bitmap().unwrap();
+        assert!(display.spi.writes.is_empty());
+
+        // Redrawing the same pixels is not a change either
+        display.fill(false);
+        display.transfer_bitmap().unwrap();
+        assert!(display.spi.writes.is_empty());
+    }
+
+    #[test]
+    fn changed_pixel_sends_one_row() {
+        let mut display = sent();
+        // Column 3 of the second module, whose rows hold columns of pixels
+        display.set_pixel(3, 10, true);
+        assert!(display.is_dirty());
+        display.transfer_bitmap().unwrap();
+        assert_eq!(
+            display.spi.writes,
+            [vec![op::DIGIT0 + 3, 0b100, op::NOOP, 0]]
+        );
+
+        display.transfer_bitmap().unwrap();
+        assert_eq!(display.spi.writes.len(), 1);
+    }
+
+    #[test]
+    fn full_refresh_sends_every_row() {
+        let mut display = sent();
+        display.force_full_refresh().unwrap();
+
+        let rows: Vec<u8> = (op::DIGIT0..=op::DIGIT7).collect();
+        assert_eq!(opcodes(&display.spi.writes, 0), rows);
+        assert_eq!(opcodes(&display.spi.writes, 1), rows);
+    }
+
+    #[test]
+    fn failed_write_resets_the_chain() {
+        let mut display = sent();
+        display.set_pixel(0, 0, true);
+        display.spi.fail = true;
+        assert_eq!(display.transfer_bitmap(), Err(ErrorKind::Other));
+        assert!(display.is_dirty());
+
+        display.transfer_bitmap().unwrap();
+        let registers = chain::CONTROL_REGISTERS.len();
+        assert_eq!(
+            opcodes(&display.spi.writes[..registers], 0),
+            chain::CONTROL_REGISTERS
+        );
+        let rows: Vec<u8> = (op::DIGIT0..=op::DIGIT7).collect();
+        assert_eq!(opcodes(&display.spi.writes[registers..], 0), rows);
+
+        // Recovered, nothing is sent again
+        display.spi.writes.clear();
+        display.transfer_bitmap().unwrap();
+        assert!(display.spi.writes.is_empty());
+    }
+
+    #[test]
+    fn reinit_cycles_through_the_registers_then_refreshes() {
+        let mut display = sent();
+        display.set_reinit_interval(Some(2));
+
+        let mut steps = Vec::new();
+        for _ in 0..2 * (chain::CONTROL_REGISTERS.len() + 2) {
+            display.transfer_bitmap().unwrap();
+            steps.push(opcodes(&std::mem::take(&mut display.spi.writes), 0));
+        }
+
+        let rows: Vec<u8> = (op::DIGIT0..=op::DIGIT7).collect();
+        let mut expected = Vec::new();
+        for &register in &chain::CONTROL_REGISTERS {
+            expected.extend([vec![], vec![register]]);
+        }
+        expected.extend([vec![], rows]);
+        expected.extend([vec![], vec![chain::CONTROL_REGISTERS[0]]]);
+        assert_eq!(steps, expected);
+    }
+}
@@ -1,13 +1,101 @@
+mod color;
+pub use color::{Palette, Rgb};
+
+mod font;
+pub use font::{FONT_3X5, FONT_8X8, Font, TextDirection, draw_text};
+
+mod layout;
+pub use layout::{ChainOrder, Layout, MODULE_SIZE, Orientation, ParseLayoutError, Rotation};
+
 mod ma72xx;
-pub use ma72xx::Max72xx;
+#[cfg(feature = "async")]
+pub use ma72xx::Max72xxAsync;
+pub use ma72xx::{Max72xx, Max72xxChain};
+
+mod monochrome;
+pub use monochrome::Monochrome;
+
+mod oled;
+pub use oled::{Oled, OledController, OledHud, OledI2c, OledInterface, OledSpi, OledSpiError};
+
+mod seven_segment;
+pub use seven_segment::{MAX_DIGITS, SevenSegment, code_b};
 
 mod text;
 pub use text::TextDisplay;
 
+/// Fake buses for the tests of the drivers
+#[cfg(test)]
+mod testing;
+
+mod ws2812;
+pub use ws2812::{LedWriter, Ws2812, Ws2812Spi};
+
 pub mod render;
 
+/// A monochrome pixel display.
+///
+/// Drawing only changes a frame buffer, [`Display::flush`] shows it.
 pub trait Display: Sized {
+    /// Error of the underlying hardware
+    type Error: std::fmt::Debug;
+
+    /// Width and height in pixels
+    fn size(&self) -> (u8, u8);
+
     fn fill(&mut self, value: bool);
 
     fn set_pixel(&mut self, x: u8, y: u8, value: bool);
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool;
+
+    /// Sends the frame buffer to the hardware
+    fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Sets the brightness, from 0 for the dimmest to 255 for the brightest setting
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error>;
+}
+
+/// A display that shows colours, see [`Monochrome`] for drawing in colour
+/// on a monochrome display.
+///
+/// Pixels that are set with [`Display::set_pixel`] or [`Display::fill`]
+/// show the colour last passed to [`ColorDisplay::set_color`].
+pub trait ColorDisplay: Display {
+    /// Colour for pixels that are turned on afterwards
+    fn set_color(&mut self, color: Rgb);
+
+    fn set_color_pixel(&mut self, x: u8, y: u8, color: Rgb);
+
+    /// Colour of a pixel, black outside of the display
+    fn get_color_pixel(&self, x: u8, y: u8) -> Rgb;
+}
+
+/// Lets adapters like [`Monochrome`] borrow a display
+impl<D: Display> Display for &mut D {
+    type Error = D::Error;
+
+    fn size(&self) -> (u8, u8) {
+        (**self).size()
+    }
+
+    fn fill(&mut self, value: bool) {
+        (**self).fill(value);
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        (**self).set_pixel(x, y, value);
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        (**self).get_pixel(x, y)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        (**self).set_brightness(brightness)
+    }
 }
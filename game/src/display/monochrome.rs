@@ -0,0 +1,130 @@
+use super::{ColorDisplay, Display, Rgb};
+
+/// Draws colours on a monochrome display by turning on the pixels
+/// whose brightest channel reaches a threshold
+pub struct Monochrome<D> {
+    display: D,
+    threshold: u8,
+}
+
+impl<D: Display> Monochrome<D> {
+    /// Turns on pixels with at least half the full brightness in one channel
+    pub fn new(display: D) -> Self {
+        Self {
+            display,
+            threshold: 128,
+        }
+    }
+
+    /// Lowest channel value that turns a pixel on, 0 turns on all of them
+    pub fn with_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.display
+    }
+
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.display
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    /// Some displays, e.g. [`super::TextDisplay`], panic on pixels outside of them
+    fn contains(&self, x: u8, y: u8) -> bool {
+        let (width, height) = self.display.size();
+        x < width && y < height
+    }
+}
+
+impl<D: Display> Display for Monochrome<D> {
+    type Error = D::Error;
+
+    fn size(&self) -> (u8, u8) {
+        self.display.size()
+    }
+
+    fn fill(&mut self, value: bool) {
+        self.display.fill(value);
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        self.display.set_pixel(x, y, value);
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        self.display.get_pixel(x, y)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.display.flush()
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.display.set_brightness(brightness)
+    }
+}
+
+impl<D: Display> ColorDisplay for Monochrome<D> {
+    /// Lit pixels always look the same on a monochrome display
+    fn set_color(&mut self, _color: Rgb) {}
+
+    fn set_color_pixel(&mut self, x: u8, y: u8, color: Rgb) {
+        if !self.contains(x, y) {
+            return;
+        }
+        let on = color.max_channel() >= self.threshold;
+        self.display.set_pixel(x, y, on);
+    }
+
+    /// Lit pixels are white
+    fn get_color_pixel(&self, x: u8, y: u8) -> Rgb {
+        if self.contains(x, y) && self.display.get_pixel(x, y) {
+            Rgb::WHITE
+        } else {
+            Rgb::BLACK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{Palette, TextDisplay};
+    use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+    #[test]
+    fn default_palette_hides_only_the_ghost() {
+        let palette = Palette::default();
+        let mut display = Monochrome::new(TextDisplay::new());
+
+        display.set_color_pixel(0, 0, palette.ghost);
+        assert_eq!(display.get_color_pixel(0, 0), Rgb::BLACK);
+
+        for (x, color) in [palette.garbage, palette.hud, palette.text]
+            .into_iter()
+            .chain(palette.pieces)
+            .enumerate()
+        {
+            display.set_color_pixel(x as u8 % DISPLAY_WIDTH, 1, color);
+            assert_eq!(
+                display.get_color_pixel(x as u8 % DISPLAY_WIDTH, 1),
+                Rgb::WHITE
+            );
+        }
+    }
+
+    #[test]
+    fn pixels_outside_of_the_display_are_black() {
+        let mut display = Monochrome::new(TextDisplay::new()).with_threshold(0);
+        display.set_color_pixel(DISPLAY_WIDTH, 0, Rgb::WHITE);
+        display.set_color_pixel(0, DISPLAY_HEIGHT, Rgb::WHITE);
+        assert_eq!(display.get_color_pixel(DISPLAY_WIDTH, 0), Rgb::BLACK);
+        assert_eq!(display.get_color_pixel(0, DISPLAY_HEIGHT), Rgb::BLACK);
+        assert!(display.inner() == &TextDisplay::new());
+    }
+}
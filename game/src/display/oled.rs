@@ -0,0 +1,572 @@
+use embedded_hal::digital::OutputPin;
+use embedded_hal::i2c::{I2c, Operation};
+use embedded_hal::spi::SpiDevice;
+
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+/// Width of the panel in pixels
+const WIDTH: u8 = 128;
+/// Height of the panel in pixels, eight rows make up a page
+const HEIGHT: u8 = 64;
+const PAGES: u8 = HEIGHT / 8;
+
+/// Blank columns between the board and the HUD
+const HUD_GAP: u8 = 2;
+
+#[allow(unused)]
+mod cmd {
+    pub const CONTRAST: u8 = 0x81;
+    pub const RESUME_RAM: u8 = 0xa4;
+    pub const NORMAL: u8 = 0xa6;
+    pub const DISPLAY_OFF: u8 = 0xae;
+    pub const DISPLAY_ON: u8 = 0xaf;
+    pub const CLOCK_DIVIDE: u8 = 0xd5;
+    pub const MULTIPLEX: u8 = 0xa8;
+    pub const DISPLAY_OFFSET: u8 = 0xd3;
+    pub const START_LINE: u8 = 0x40;
+    pub const SEGMENT_REMAP: u8 = 0xa1;
+    pub const COM_SCAN_DEC: u8 = 0xc8;
+    pub const COM_PINS: u8 = 0xda;
+    pub const PRECHARGE: u8 = 0xd9;
+    pub const VCOM_DESELECT: u8 = 0xdb;
+    /// SSD1306 only
+    pub const ADDRESSING_MODE: u8 = 0x20;
+    /// SSD1306 only
+    pub const CHARGE_PUMP: u8 = 0x8d;
+    /// SH1106 only
+    pub const DC_DC: u8 = 0xad;
+    pub const PAGE_START: u8 = 0xb0;
+    pub const COLUMN_LOW: u8 = 0x00;
+    pub const COLUMN_HIGH: u8 = 0x10;
+}
+
+/// The controller chips of 128x64 OLED panels, which differ in their setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OledController {
+    Ssd1306,
+    /// Has 132 columns of memory, the panel shows the middle 128
+    Sh1106,
+}
+
+impl OledController {
+    /// First column of memory that is visible
+    const fn column_offset(self) -> u8 {
+        match self {
+            OledController::Ssd1306 => 0,
+            OledController::Sh1106 => 2,
+        }
+    }
+}
+
+/// The bus an OLED controller is connected with
+pub trait OledInterface {
+    type Error: std::fmt::Debug;
+
+    fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes to the display memory, starting at the current page and column
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Connection through I2C, where a control byte tells commands from data
+pub struct OledI2c<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C: I2c> OledI2c<I2C> {
+    /// Address of most modules, the others use `0x3d`
+    pub const DEFAULT_ADDRESS: u8 = 0x3c;
+
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<I2C: I2c> OledInterface for OledI2c<I2C> {
+    type Error = I2C::Error;
+
+    fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.transaction(
+            self.address,
+            &mut [Operation::Write(&[0x00]), Operation::Write(commands)],
+        )
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.transaction(
+            self.address,
+            &mut [Operation::Write(&[0x40]), Operation::Write(data)],
+        )
+    }
+}
+
+/// Error of the SPI connection, which also drives the data/command pin
+#[derive(Debug)]
+pub enum OledSpiError<SPI, PIN> {
+    Spi(SPI),
+    Pin(PIN),
+}
+
+/// Connection through 4-wire SPI, where a pin tells commands from data
+pub struct OledSpi<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+}
+
+impl<SPI: SpiDevice, DC: OutputPin> OledSpi<SPI, DC> {
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self { spi, dc }
+    }
+}
+
+impl<SPI: SpiDevice, DC: OutputPin> OledInterface for OledSpi<SPI, DC> {
+    type Error = OledSpiError<SPI::Error, DC::Error>;
+
+    fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_low().map_err(OledSpiError::Pin)?;
+        self.spi.write(commands).map_err(OledSpiError::Spi)
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_high().map_err(OledSpiError::Pin)?;
+        self.spi.write(data).map_err(OledSpiError::Spi)
+    }
+}
+
+/// A 128x64 OLED panel showing the board on the left and a HUD to the right of it.
+///
+/// As a [`Display`](super::Display) it is the size of the board, each of its pixels
+/// is drawn as a square of `scale` by `scale` pixels. [`Oled::hud`] draws next to it.
+pub struct Oled<DI> {
+    interface: DI,
+    controller: OledController,
+    /// One byte per column and page, the lowest bit is the top row of the page
+    buffer: Vec<u8>,
+    /// The buffer as it was last sent
+    sent: Vec<u8>,
+    /// Whether the next flush has to send every page, e.g. because the
+    /// content of the display memory is unknown
+    refresh_all: bool,
+    scale: u8,
+}
+
+impl<DI: OledInterface> Oled<DI> {
+    /// Draws the board twice its size, which fills the height of the panel
+    pub fn new(interface: DI, controller: OledController) -> Self {
+        let bytes = WIDTH as usize * PAGES as usize;
+        Self {
+            interface,
+            controller,
+            buffer: vec![0x00; bytes],
+            sent: vec![0x00; bytes],
+            refresh_all: true,
+            scale: 2,
+        }
+    }
+
+    /// Size of a board pixel on the panel, reduced until the board fits
+    pub fn with_scale(mut self, scale: u8) -> Self {
+        let largest = (WIDTH / DISPLAY_WIDTH).min(HEIGHT / DISPLAY_HEIGHT);
+        self.scale = scale.clamp(1, largest);
+        self
+    }
+
+    pub fn interface(&self) -> &DI {
+        &self.interface
+    }
+
+    /// Sets up the controller and turns the panel on, the whole frame is sent
+    /// again with the next flush
+    pub fn reset(&mut self) -> Result<(), DI::Error> {
+        self.refresh_all = true;
+
+        let power: &[u8] = match self.controller {
+            // Internal charge pump and page addressing, which the SH1106 always uses
+            OledController::Ssd1306 => &[cmd::CHARGE_PUMP, 0x14, cmd::ADDRESSING_MODE, 0x02],
+            OledController::Sh1106 => &[cmd::DC_DC, 0x8b],
+        };
+        let precharge = match self.controller {
+            OledController::Ssd1306 => 0xf1,
+            OledController::Sh1106 => 0x22,
+        };
+
+        self.interface.send_commands(&[
+            cmd::DISPLAY_OFF,
+            cmd::CLOCK_DIVIDE,
+            0x80,
+            cmd::MULTIPLEX,
+            HEIGHT - 1,
+            cmd::DISPLAY_OFFSET,
+            0x00,
+            cmd::START_LINE,
+        ])?;
+        self.interface.send_commands(power)?;
+        self.interface.send_commands(&[
+            // Column 0 is on the left and row 0 at the top
+            cmd::SEGMENT_REMAP,
+            cmd::COM_SCAN_DEC,
+            cmd::COM_PINS,
+            0x12,
+            cmd::CONTRAST,
+            0x7f,
+            cmd::PRECHARGE,
+            precharge,
+            cmd::VCOM_DESELECT,
+            0x40,
+            cmd::RESUME_RAM,
+            cmd::NORMAL,
+            cmd::DISPLAY_ON,
+        ])
+    }
+
+    /// Sends the pages that changed since the last flush
+    pub fn flush(&mut self) -> Result<(), DI::Error> {
+        let column = self.controller.column_offset();
+        for page in 0..PAGES {
+            let range = page as usize * WIDTH as usize..(page as usize + 1) * WIDTH as usize;
+            if !self.refresh_all && self.buffer[range.clone()] == self.sent[range.clone()] {
+                continue;
+            }
+
+            self.interface.send_commands(&[
+                cmd::PAGE_START | page,
+                cmd::COLUMN_LOW | (column & 0x0f),
+                cmd::COLUMN_HIGH | (column >> 4),
+            ])?;
+            self.interface.send_data(&self.buffer[range.clone()])?;
+            self.sent[range.clone()].copy_from_slice(&self.buffer[range]);
+        }
+        self.refresh_all = false;
+        Ok(())
+    }
+
+    /// The region to the right of the board, with one panel pixel per pixel
+    pub fn hud(&mut self) -> OledHud<'_, DI> {
+        let left = DISPLAY_WIDTH * self.scale + HUD_GAP;
+        OledHud { oled: self, left }
+    }
+
+    fn set_panel_pixel(&mut self, x: u8, y: u8, value: bool) {
+        if x >= WIDTH || y >= HEIGHT {
+            return;
+        }
+        let index = (y / 8) as usize * WIDTH as usize + x as usize;
+        let mask = 1 << (y % 8);
+        if value {
+            self.buffer[index] |= mask;
+        } else {
+            self.buffer[index] &= !mask;
+        }
+    }
+
+    fn get_panel_pixel(&self, x: u8, y: u8) -> bool {
+        if x >= WIDTH || y >= HEIGHT {
+            return false;
+        }
+        let index = (y / 8) as usize * WIDTH as usize + x as usize;
+        self.buffer[index] & (1 << (y % 8)) != 0
+    }
+
+    /// Contrast from 0 to 255
+    fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.interface.send_commands(&[cmd::CONTRAST, contrast])
+    }
+}
+
+impl<DI: OledInterface> super::Display for Oled<DI> {
+    type Error = DI::Error;
+
+    fn size(&self) -> (u8, u8) {
+        (DISPLAY_WIDTH, DISPLAY_HEIGHT)
+    }
+
+    /// Only fills the board, the HUD keeps its content
+    fn fill(&mut self, value: bool) {
+        for y in 0..DISPLAY_HEIGHT {
+            for x in 0..DISPLAY_WIDTH {
+                self.set_pixel(x, y, value);
+            }
+        }
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        if x >= DISPLAY_WIDTH || y >= DISPLAY_HEIGHT {
+            return;
+        }
+        for dy in 0..self.scale {
+            for dx in 0..self.scale {
+                self.set_panel_pixel(x * self.scale + dx, y * self.scale + dy, value);
+            }
+        }
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        x < DISPLAY_WIDTH
+            && y < DISPLAY_HEIGHT
+            && self.get_panel_pixel(x * self.scale, y * self.scale)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Oled::flush(self)
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.set_contrast(brightness)
+    }
+}
+
+/// Draws into the HUD of an [`Oled`], flushing sends the whole panel
+pub struct OledHud<'a, DI> {
+    oled: &'a mut Oled<DI>,
+    /// First column of the HUD on the panel
+    left: u8,
+}
+
+impl<DI: OledInterface> super::Display for OledHud<'_, DI> {
+    type Error = DI::Error;
+
+    fn size(&self) -> (u8, u8) {
+        (WIDTH.saturating_sub(self.left), HEIGHT)
+    }
+
+    fn fill(&mut self, value: bool) {
+        for y in 0..HEIGHT {
+            for x in self.left..WIDTH {
+                self.oled.set_panel_pixel(x, y, value);
+            }
+        }
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        let Some(x) = x.checked_add(self.left) else {
+            return;
+        };
+        self.oled.set_panel_pixel(x, y, value);
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        x.checked_add(self.left)
+            .is_some_and(|x| self.oled.get_panel_pixel(x, y))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.oled.flush()
+    }
+
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.oled.set_contrast(brightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::Display;
+    use crate::display::testing::{I2cRecorder, PinRecorder, SpiRecorder};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Sent {
+        Commands(Vec<u8>),
+        Data(Vec<u8>),
+    }
+
+    /// Records everything sent to the controller
+    #[derive(Default)]
+    struct Recorder {
+        sent: Vec<Sent>,
+    }
+
+    impl OledInterface for Recorder {
+        type Error = std::convert::Infallible;
+
+        fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+            self.sent.push(Sent::Commands(commands.to_vec()));
+            Ok(())
+        }
+
+        fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+            self.sent.push(Sent::Data(data.to_vec()));
+            Ok(())
+        }
+    }
+
+    /// A panel that was reset and flushed once, with nothing recorded yet
+    fn flushed(controller: OledController) -> Oled<Recorder> {
+        let mut oled = Oled::new(Recorder::default(), controller);
+        oled.reset().unwrap();
+        oled.flush().unwrap();
+        oled.interface.sent.clear();
+        oled
+    }
+
+    /// The pages sent by the recorded flush, along with their data
+    fn pages(sent: &[Sent], column: u8) -> Vec<(u8, Vec<u8>)> {
+        sent.chunks(2)
+            .map(|chunk| match chunk {
+                [Sent::Commands(commands), Sent::Data(data)] => {
+                    assert_eq!(commands[1..], [column & 0x0f, 0x10 | (column >> 4)]);
+                    (commands[0] - cmd::PAGE_START, data.clone())
+                }
+                _ => panic!("unexpected flush {chunk:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resets_ssd1306() {
+        let mut oled = Oled::new(Recorder::default(), OledController::Ssd1306);
+        oled.reset().unwrap();
+        assert_eq!(
+            oled.interface().sent,
+            [
+                Sent::Commands(vec![0xae, 0xd5, 0x80, 0xa8, 0x3f, 0xd3, 0x00, 0x40]),
+                Sent::Commands(vec![0x8d, 0x14, 0x20, 0x02]),
+                Sent::Commands(vec![
+                    0xa1, 0xc8, 0xda, 0x12, 0x81, 0x7f, 0xd9, 0xf1, 0xdb, 0x40, 0xa4, 0xa6, 0xaf
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn resets_sh1106() {
+        let mut oled = Oled::new(Recorder::default(), OledController::Sh1106);
+        oled.reset().unwrap();
+        assert_eq!(
+            oled.interface().sent,
+            [
+                Sent::Commands(vec![0xae, 0xd5, 0x80, 0xa8, 0x3f, 0xd3, 0x00, 0x40]),
+                Sent::Commands(vec![0xad, 0x8b]),
+                Sent::Commands(vec![
+                    0xa1, 0xc8, 0xda, 0x12, 0x81, 0x7f, 0xd9, 0x22, 0xdb, 0x40, 0xa4, 0xa6, 0xaf
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_flush_sends_every_page() {
+        for (controller, column) in [(OledController::Ssd1306, 0), (OledController::Sh1106, 2)] {
+            let mut oled = Oled::new(Recorder::default(), controller);
+            oled.flush().unwrap();
+
+            let pages = pages(&oled.interface().sent, column);
+            assert_eq!(
+                pages.iter().map(|(page, _)| *page).collect::<Vec<_>>(),
+                (0..PAGES).collect::<Vec<_>>()
+            );
+            assert!(pages.iter().all(|(_, data)| *data == [0; WIDTH as usize]));
+        }
+    }
+
+    #[test]
+    fn sh1106_starts_at_column_two() {
+        let mut oled = Oled::new(Recorder::default(), OledController::Sh1106);
+        oled.flush().unwrap();
+        assert_eq!(
+            oled.interface().sent[0],
+            Sent::Commands(vec![cmd::PAGE_START, cmd::COLUMN_LOW | 2, cmd::COLUMN_HIGH])
+        );
+    }
+
+    #[test]
+    fn unchanged_frames_send_nothing() {
+        let mut oled = flushed(OledController::Ssd1306);
+        oled.set_pixel(3, 3, false);
+        oled.flush().unwrap();
+        assert!(oled.interface().sent.is_empty());
+    }
+
+    #[test]
+    fn sends_only_the_changed_page() {
+        let mut oled = flushed(OledController::Sh1106);
+        // Rows 10 and 11 of the panel at twice the size
+        oled.set_pixel(1, 5, true);
+        oled.flush().unwrap();
+
+        let pages = pages(&oled.interface().sent, 2);
+        assert_eq!(pages.len(), 1);
+        let (page, data) = &pages[0];
+        assert_eq!(*page, 1);
+        assert_eq!(data[..4], [0, 0, 0b1100, 0b1100]);
+        assert!(data[4..].iter().all(|&byte| byte == 0));
+        assert!(oled.get_pixel(1, 5));
+    }
+
+    #[test]
+    fn hud_starts_right_of_the_board() {
+        for scale in [1, 2] {
+            let mut oled = flushed(OledController::Ssd1306).with_scale(scale);
+            let left = DISPLAY_WIDTH * scale + HUD_GAP;
+
+            let mut hud = oled.hud();
+            assert_eq!(hud.size(), (WIDTH - left, HEIGHT));
+            hud.set_pixel(0, 9, true);
+            assert!(hud.get_pixel(0, 9));
+            hud.flush().unwrap();
+
+            let pages = pages(&oled.interface().sent, 0);
+            assert_eq!(pages.len(), 1);
+            let (page, data) = &pages[0];
+            assert_eq!(*page, 1);
+            assert_eq!(data[left as usize], 0b10);
+            assert_eq!(data.iter().filter(|&&byte| byte != 0).count(), 1);
+            // The board is left alone
+            assert!(!oled.get_pixel(DISPLAY_WIDTH - 1, 4));
+        }
+    }
+
+    #[test]
+    fn i2c_prefixes_commands_and_data_with_control_bytes() {
+        let mut interface = OledI2c::new(I2cRecorder::default(), 0x3d);
+        interface.send_commands(&[cmd::DISPLAY_ON]).unwrap();
+        interface.send_data(&[0x12, 0x34]).unwrap();
+        assert_eq!(
+            interface.i2c.writes,
+            [
+                (0x3d, vec![0x00, cmd::DISPLAY_ON]),
+                (0x3d, vec![0x40, 0x12, 0x34]),
+            ]
+        );
+    }
+
+    #[test]
+    fn spi_pulls_data_command_low_for_commands() {
+        let mut interface = OledSpi::new(SpiRecorder::default(), PinRecorder::default());
+        interface.send_commands(&[cmd::DISPLAY_OFF]).unwrap();
+        assert_eq!(interface.dc.levels, [false]);
+        assert_eq!(interface.spi.writes, [[cmd::DISPLAY_OFF]]);
+
+        interface.send_data(&[0xff, 0x00]).unwrap();
+        assert_eq!(interface.dc.levels, [false, true]);
+        assert_eq!(interface.spi.writes[1], [0xff, 0x00]);
+
+        interface.spi.fail = true;
+        assert!(matches!(
+            interface.send_data(&[0x01]),
+            Err(OledSpiError::Spi(_))
+        ));
+    }
+
+    #[test]
+    fn flushes_through_i2c() {
+        let mut oled = Oled::new(
+            OledI2c::new(I2cRecorder::default(), 0x3c),
+            OledController::Sh1106,
+        );
+        oled.flush().unwrap();
+
+        let writes = &oled.interface().i2c.writes;
+        assert_eq!(writes.len(), 2 * PAGES as usize);
+        assert_eq!(
+            writes[0],
+            (
+                0x3c,
+                vec![0x00, cmd::PAGE_START, cmd::COLUMN_LOW | 2, cmd::COLUMN_HIGH]
+            )
+        );
+        assert_eq!(writes[1].1.len(), 1 + WIDTH as usize);
+        assert_eq!(writes[1].1[0], 0x40);
+    }
+}
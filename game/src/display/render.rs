@@ -1,100 +1,206 @@
-use crate::display::Display;
+use crate::display::{
+    ColorDisplay, Display, FONT_3X5, FONT_8X8, Monochrome, Palette, Rgb, SevenSegment,
+    TextDirection, draw_text,
+};
 use crate::logic::piece::Piece;
-use crate::logic::{GameState, InGameState, InStartState, StartMenuPhase};
+use crate::logic::puzzle::bundled_puzzles;
+use crate::logic::{
+    GameMode, GameOverAnimation, GameState, InGameOverState, InGameState, InHighscoreEntryState,
+    InStartState, MenuItem,
+};
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
-use std::time::{Duration, Instant};
 
-pub fn render(game_state: &mut GameState, display: &mut impl Display) {
+pub fn render(game_state: &GameState, display: &mut impl Display) {
     match game_state {
         GameState::StartMenu(state) => render_start(state, display),
-        GameState::InGame(state) => render_in_game(state, display),
-        GameState::GameOver(score) => render_score(*score, display),
+        GameState::InGame(state) => {
+            render_in_game(state, &mut Monochrome::new(display), &Palette::default());
+        }
+        GameState::GameOver(state) => render_game_over(state, display),
+        GameState::HighscoreEntry(state) => render_highscore_entry(state, display),
     }
 }
 
-fn render_start(state: &mut InStartState, display: &mut impl Display) {
-    // Bestimme die für die aktuelle Phase benötigte Verzögerung
-    let required_delay = match state.phase {
-        StartMenuPhase::Text => Duration::from_millis(3000),
-        _ => Duration::from_millis(500),
+/// Like [`render`], with every piece in its own colour and a ghost showing
+/// where the current piece lands. Screens outside the game use the text colour.
+pub fn render_color(game_state: &GameState, display: &mut impl ColorDisplay, palette: &Palette) {
+    match game_state {
+        GameState::InGame(state) => render_in_game(state, display, palette),
+        _ => {
+            display.set_color(palette.text);
+            render(game_state, display);
+        }
+    }
+}
+
+/// Shows level, lines and score on an 8 digit 7-segment display as `LL.NN.SSSS`.
+/// Only the score is known after the game, it then takes all digits.
+/// Nothing is shown in the start menu.
+pub fn render_stats<SPI>(game_state: &GameState, display: &mut SevenSegment<SPI>) {
+    display.clear();
+
+    let Some(score) = game_state.score() else {
+        return;
     };
 
-    if state.last_update.elapsed() >= required_delay {
-        let next_phase = match state.phase {
-            StartMenuPhase::Text => StartMenuPhase::ButtonStart,
-            StartMenuPhase::ButtonStart => StartMenuPhase::ButtonPressed,
-            StartMenuPhase::ButtonPressed => StartMenuPhase::ButtonReleased,
-            StartMenuPhase::ButtonReleased => StartMenuPhase::Text,
+    // Each value keeps its lowest digits when it grows too large
+    let text = match game_state {
+        GameState::InGame(state) => {
+            format!(
+                "{:>2}.{:>2}.{:>4}",
+                state.level % 100,
+                state.lines % 100,
+                score % 10_000
+            )
+        }
+        _ => format!("{:>8}", score % 100_000_000),
+    };
+    display.write_str(0, &text);
+}
+
+/// Shows score, level and lines as text, e.g. next to the board on an [`Oled`].
+/// Only the score is known after the game, nothing is shown in the start menu.
+///
+/// [`Oled`]: crate::display::Oled
+pub fn render_hud(game_state: &GameState, display: &mut impl Display) {
+    display.fill(false);
+
+    let Some(score) = game_state.score() else {
+        return;
+    };
+
+    render_line("SCORE", 0, display);
+    render_line(&score.to_string(), 1, display);
+    if let GameState::InGame(state) = game_state {
+        render_line(&format!("LEVEL {}", state.level), 3, display);
+        render_line(&format!("LINES {}", state.lines), 4, display);
+        if state.paused {
+            render_line("PAUSE", 6, display);
+        }
+    }
+}
+
+/// Renders the text from the left in the given line of 8 pixels
+fn render_line(text: &str, line: u8, display: &mut impl Display) {
+    let y = 8 * line as i16;
+    draw_text(display, &FONT_8X8, text, 0, y, TextDirection::Horizontal);
+}
+
+fn render_start(state: &InStartState, display: &mut impl Display) {
+    if state.showing_highscores {
+        let entries = &state.highscores.entries;
+        let text = if entries.is_empty() {
+            "NONE".to_string()
+        } else {
+            entries
+                .iter()
+                .enumerate()
+                .map(|(rank, entry)| {
+                    let initials = String::from_iter(entry.initials);
+                    format!("{}-{initials} {}", rank + 1, entry.score)
+                })
+                .reduce(|accum, elem| accum + " " + &elem)
+                .unwrap_or_default()
         };
+        render_text(&text, state.ticks, display);
+        return;
+    }
 
-        // Perform the render action for the *new* phase
-        // (or you could do it for the old one, depending on the desired behavior)
-        // Here we render based on the state we're transitioning to.
-        match next_phase {
-            StartMenuPhase::Text => {
-                display.fill(false); // Clear screen before redrawing
-                for i in 0..4 {
-                    render_letter(i, 8 * i, display);
-                }
-            }
-            StartMenuPhase::ButtonStart => {
-                display.fill(false);
-                for i in 0..4 {
-                    render_button(true, 8 * i, display);
-                }
+    match state.item {
+        MenuItem::Play => render_title(state.ticks, display),
+        MenuItem::Mode => match state.mode {
+            GameMode::Marathon => render_text("MARATHON", state.ticks, display),
+            GameMode::Puzzle(index) => render_puzzle_selection(index, display),
+        },
+        MenuItem::Level => {
+            let text = format!("LEVEL {}", state.rules.start_level);
+            render_text(&text, state.ticks, display);
+        }
+        MenuItem::Wrap => {
+            let text = if state.rules.wrap {
+                "WRAP ON"
+            } else {
+                "WRAP OFF"
+            };
+            render_text(text, state.ticks, display);
+        }
+        MenuItem::Highscores => render_text("SCORES", state.ticks, display),
+    }
+}
+
+/// Shows the title, followed by a button being pressed as a hint to press rotate
+fn render_title(ticks: u32, display: &mut impl Display) {
+    display.fill(false);
+    match ticks % 80 {
+        0..50 => {
+            let (width, _) = display.size();
+            for (i, text) in ["TE", "TR", "IS"].iter().enumerate() {
+                let length = FONT_3X5.text_length(text, TextDirection::Horizontal);
+                let x = (width as i16 - length as i16) / 2;
+                let y = 3 + 10 * i as i16;
+                draw_text(display, &FONT_3X5, text, x, y, TextDirection::Horizontal);
             }
-            StartMenuPhase::ButtonPressed => {
-                display.fill(false);
-                for i in 0..4 {
-                    render_button(false, 8 * i, display);
-                }
+        }
+        50..60 | 70..80 => {
+            for i in 0..4 {
+                render_button(true, 8 * i, display);
             }
-            StartMenuPhase::ButtonReleased => {
-                display.fill(false);
-                for i in 0..4 {
-                    render_button(true, 8 * i, display);
-                }
+        }
+        _ => {
+            for i in 0..4 {
+                render_button(false, 8 * i, display);
             }
         }
-        state.phase = next_phase;
-        state.last_update = Instant::now();
     }
 }
 
-/// Gets and renders the bitmap for a letter.
-fn render_letter(row: u8, offset: u8, display: &mut impl Display) {
-    let bitmap = letter_bitmap(row);
-    render_bitmap_rows(&bitmap, offset, display);
+/// Renders the characters of the text below each other. Text that does not
+/// fit on the display scrolls upwards by one row per tick.
+fn render_text(text: &str, ticks: u32, display: &mut impl Display) {
+    display.fill(false);
+    let (_, height) = display.size();
+
+    let length = FONT_8X8.text_length(text, TextDirection::Vertical);
+    if length <= height as u16 {
+        draw_text(display, &FONT_8X8, text, 0, 0, TextDirection::Vertical);
+        return;
+    }
+
+    // One blank character separates the end of the text from its start
+    let scroll_height = length as u32 + FONT_8X8.size().1 as u32;
+    let y = -((ticks % scroll_height) as i16);
+    for y in [y, y + scroll_height as i16] {
+        draw_text(display, &FONT_8X8, text, 0, y, TextDirection::Vertical);
+    }
 }
 
-/// Gets and renders the bitmap for a letter.
+/// Shows the number of the selected puzzle above a preview of its board
+fn render_puzzle_selection(index: usize, display: &mut impl Display) {
+    display.fill(false);
+    let (width, height) = display.size();
+
+    let number = format!("{:02}", (index % 100 + 1) % 100);
+    draw_text(display, &FONT_8X8, &number, 0, 0, TextDirection::Vertical);
+
+    let Some(puzzle) = bundled_puzzles().get(index) else {
+        return;
+    };
+    let rows = &puzzle.rows;
+    // Bottom up, as far as the display reaches
+    for (row, y) in rows.iter().rev().take(16).zip((0..height).rev()) {
+        for x in 0..width.min(8) {
+            let mask = 0b1000_0000 >> x;
+            display.set_pixel(x, y, row & mask != 0);
+        }
+    }
+}
+
+/// Gets and renders the bitmap for a button.
 fn render_button(pressed: bool, offset: u8, display: &mut impl Display) {
     let bitmap = button_bitmap(pressed);
     render_bitmap_rows(&bitmap, offset, display);
 }
 
-const fn letter_bitmap(c: u8) -> [u8; 8] {
-    match c {
-        0 => [
-            0b01111110, 0b00011000, 0b00011000, 0b00011000, 0b00000000, 0b01111110, 0b01100000,
-            0b01111100,
-        ],
-        1 => [
-            0b01100000, 0b01111110, 0b00000000, 0b01111110, 0b00011000, 0b00011000, 0b00011000,
-            0b00000000,
-        ],
-        2 => [
-            0b01111100, 0b01101100, 0b01111100, 0b01101100, 0b01100110, 0b00000000, 0b00011000,
-            0b00011000,
-        ],
-        3 => [
-            0b00011000, 0b00011000, 0b00000000, 0b01111110, 0b01100000, 0b01111110, 0b00000110,
-            0b01111110,
-        ],
-        _ => [0; 8],
-    }
-}
-
 const fn button_bitmap(b: bool) -> [u8; 8] {
     match b {
         true => [
@@ -108,28 +214,64 @@ const fn button_bitmap(b: bool) -> [u8; 8] {
     }
 }
 
-fn render_in_game(state: &InGameState, display: &mut impl Display) {
+fn render_in_game(state: &InGameState, display: &mut impl ColorDisplay, palette: &Palette) {
     for x in 0..DISPLAY_WIDTH {
         for y in 0..DISPLAY_HEIGHT {
-            display.set_pixel(x, y, state.blocks.get(x as i16, y as i16));
+            let (x_block, y_block) = (x as i16, y as i16);
+            let color = if state.blocks.get(x_block, y_block) {
+                state
+                    .blocks
+                    .kind_name(x_block, y_block)
+                    .map_or(palette.garbage, |name| palette.piece(name))
+            } else {
+                Rgb::BLACK
+            };
+            display.set_color_pixel(x, y, color);
+        }
+    }
+
+    match &state.line_clear {
+        // The current piece is already part of the blocks while rows are cleared
+        Some(line_clear) => {
+            let center = DISPLAY_WIDTH / 2;
+            for &y in &line_clear.rows {
+                for x in center - line_clear.wiped..center + line_clear.wiped {
+                    display.set_color_pixel(x, y, Rgb::BLACK);
+                }
+            }
+        }
+        None => {
+            render_piece(&state.drop_position(), palette.ghost, display);
+            let color = palette.piece(state.current_piece.kind().name);
+            render_piece(&state.current_piece, color, display);
         }
     }
 
-    render_piece(&state.current_piece, display);
+    let (width, _) = display.size();
 
     // Divider for next piece
-    for i in 0..DISPLAY_WIDTH {
-        display.set_pixel(i, 7, true);
+    for i in 0..width {
+        display.set_color_pixel(i, 7, palette.hud);
     }
 
-    if let Some(next_piece) = &state.next_piece {
-        render_piece(next_piece, display);
+    if state.paused {
+        // The preview region shows a P instead of the next piece
+        for y in 0..7 {
+            for x in 0..width {
+                display.set_color_pixel(x, y, Rgb::BLACK);
+            }
+        }
+        display.set_color(palette.hud);
+        draw_text(display, &FONT_8X8, "P", 0, 0, TextDirection::Horizontal);
+    } else if let Some(next_piece) = &state.next_piece {
+        let color = palette.piece(next_piece.kind().name);
+        render_piece(next_piece, color, display);
     }
 }
 
-fn render_piece(piece: &Piece, display: &mut impl Display) {
+fn render_piece(piece: &Piece, color: Rgb, display: &mut impl ColorDisplay) {
     for (x, y) in piece.block_positions() {
-        display.set_pixel(wrap_x(x), y as u8, true);
+        display.set_color_pixel(wrap_x(x), y as u8, color);
     }
 }
 
@@ -137,72 +279,64 @@ pub fn wrap_x(x: i16) -> u8 {
     (x.rem_euclid(8)) as u8
 }
 
-fn render_score(score: u32, display: &mut impl Display) {
+fn render_game_over(state: &InGameOverState, display: &mut impl Display) {
+    let (width, height) = display.size();
+    match state.animation {
+        GameOverAnimation::Fill(rows) => {
+            for y in 0..height {
+                let filled = y >= height.saturating_sub(rows);
+                for x in 0..width {
+                    display.set_pixel(x, y, filled || state.blocks.get(x as i16, y as i16));
+                }
+            }
+        }
+        GameOverAnimation::Clear(rows) => {
+            display.fill(false);
+            for y in 0..height.saturating_sub(rows) {
+                for x in 0..width {
+                    display.set_pixel(x, y, true);
+                }
+            }
+        }
+        GameOverAnimation::Score { highlight } => {
+            render_score(state.score, display);
+
+            // The digits leave the outer columns free for the new highscore bars
+            if highlight {
+                for y in 0..height {
+                    display.set_pixel(0, y, true);
+                    display.set_pixel(width - 1, y, true);
+                }
+            }
+        }
+    }
+}
+
+/// Shows the three initials below each other, underlining the one being edited
+fn render_highscore_entry(state: &InHighscoreEntryState, display: &mut impl Display) {
     display.fill(false);
 
-    for (i, digit) in [
-        score / 1000 % 10,
-        score / 100 % 10,
-        score / 10 % 10,
-        score % 10,
-    ]
-    .iter()
-    .enumerate()
-    {
+    let initials = String::from_iter(state.initials);
+    draw_text(display, &FONT_8X8, &initials, 0, 0, TextDirection::Vertical);
+
+    for i in 0..state.initials.len() {
         let offset = 8 * i as u8;
-        render_digit(*digit, offset, display);
+        if i == state.position && state.cursor_visible {
+            for x in 0..display.size().0 {
+                display.set_pixel(x, offset + 7, true);
+            }
+        }
     }
-}
 
-fn render_digit(digit: u32, offset: u8, display: &mut impl Display) {
-    let bitmap = digit_bitmap(digit);
-    render_bitmap_rows(&bitmap, offset, display);
+    // The last character shows the confirm button
+    render_button(true, 24, display);
 }
 
-const fn digit_bitmap(digit: u32) -> [u8; 8] {
-    match digit {
-        0 => [
-            0b00111100, 0b01100110, 0b01101110, 0b01110110, 0b01100110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        1 => [
-            0b00011000, 0b00111000, 0b00011000, 0b00011000, 0b00011000, 0b00011000, 0b00111100,
-            0b00000000,
-        ],
-        2 => [
-            0b00111100, 0b01100110, 0b00000110, 0b00001100, 0b00011000, 0b01100000, 0b01111110,
-            0b00000000,
-        ],
-        3 => [
-            0b00111100, 0b01100110, 0b00000110, 0b00011100, 0b00000110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        4 => [
-            0b00001100, 0b00011100, 0b00101100, 0b01001100, 0b01111110, 0b00001100, 0b00001100,
-            0b00000000,
-        ],
-        5 => [
-            0b01111110, 0b01100000, 0b01111100, 0b00000110, 0b00000110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        6 => [
-            0b00111100, 0b01100110, 0b01100000, 0b01111100, 0b01100110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        7 => [
-            0b01111110, 0b01100110, 0b00000110, 0b00001100, 0b00011000, 0b00011000, 0b00011000,
-            0b00000000,
-        ],
-        8 => [
-            0b00111100, 0b01100110, 0b01100110, 0b00111100, 0b01100110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        9 => [
-            0b00111100, 0b01100110, 0b01100110, 0b00111110, 0b00000110, 0b01100110, 0b00111100,
-            0b00000000,
-        ],
-        _ => unreachable!(),
-    }
+fn render_score(score: u32, display: &mut impl Display) {
+    display.fill(false);
+
+    let digits = format!("{:04}", score % 10000);
+    draw_text(display, &FONT_8X8, &digits, 0, 0, TextDirection::Vertical);
 }
 
 fn render_bitmap_rows(bitmap: &[u8; 8], offset_y: u8, display: &mut impl Display) {
@@ -216,3 +350,60 @@ fn render_bitmap_rows(bitmap: &[u8; 8], offset_y: u8, display: &mut impl Display
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::TextDisplay;
+
+    /// A display of a single 8x8 module
+    struct Module([[bool; 8]; 8]);
+
+    impl Display for Module {
+        type Error = std::convert::Infallible;
+
+        fn size(&self) -> (u8, u8) {
+            (8, 8)
+        }
+
+        fn fill(&mut self, value: bool) {
+            self.0 = [[value; 8]; 8];
+        }
+
+        fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+            self.0[y as usize][x as usize] = value;
+        }
+
+        fn get_pixel(&self, x: u8, y: u8) -> bool {
+            self.0[y as usize][x as usize]
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_brightness(&mut self, _brightness: u8) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn puzzle_preview_fits_a_single_module() {
+        for index in 0..bundled_puzzles().len() {
+            let mut display = Module([[false; 8]; 8]);
+            render_puzzle_selection(index, &mut display);
+
+            let bottom = bundled_puzzles()[index].rows.last().unwrap();
+            let shown = (0..8).fold(0, |row, x| row << 1 | display.get_pixel(x, 7) as u8);
+            assert_eq!(shown, *bottom);
+        }
+    }
+
+    #[test]
+    fn unknown_puzzle_shows_only_its_number() {
+        let mut display = TextDisplay::new();
+        render_puzzle_selection(usize::MAX, &mut display);
+        let (_, height) = display.size();
+        assert!((16..height).all(|y| (0..8).all(|x| !display.get_pixel(x, y))));
+    }
+}
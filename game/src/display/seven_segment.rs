@@ -0,0 +1,208 @@
+use embedded_hal::spi::SpiDevice;
+
+use super::ma72xx::{DecodeMode, op};
+
+/// Most digits a single MAX7219 drives
+pub const MAX_DIGITS: u8 = 8;
+
+/// Code-B value of a blank digit
+const BLANK: u8 = 0x0f;
+
+/// Decimal point, added to the Code-B value
+const DOT: u8 = 0x80;
+
+/// A MAX7219 driving up to eight 7-segment digits through its Code-B decoder.
+///
+/// Digits are numbered from the right, digit 0 is wired to DIG0.
+pub struct SevenSegment<SPI> {
+    spi: SPI,
+    /// Code-B values including the decimal point
+    digits: [u8; MAX_DIGITS as usize],
+    /// The digits as they were last sent, `None` when unknown
+    sent: [Option<u8>; MAX_DIGITS as usize],
+    count: u8,
+    intensity: u8,
+}
+
+/// Code-B value for a character, `None` for characters the decoder cannot show
+pub const fn code_b(c: char) -> Option<u8> {
+    match c {
+        '0'..='9' => Some(c as u8 - b'0'),
+        '-' => Some(0x0a),
+        'E' | 'e' => Some(0x0b),
+        'H' | 'h' => Some(0x0c),
+        'L' | 'l' => Some(0x0d),
+        'P' | 'p' => Some(0x0e),
+        ' ' => Some(BLANK),
+        _ => None,
+    }
+}
+
+impl<E, SPI: SpiDevice<Error = E>> SevenSegment<SPI> {
+    /// A chip with `count` digits connected, at most [`MAX_DIGITS`]
+    pub fn new(spi: SPI, count: u8) -> Self {
+        Self {
+            spi,
+            digits: [BLANK; MAX_DIGITS as usize],
+            sent: [None; MAX_DIGITS as usize],
+            count: count.clamp(1, MAX_DIGITS),
+            // A medium brightness
+            intensity: 2,
+        }
+    }
+
+    /// Writes all control registers, the digits are sent again with the next flush
+    pub fn reset(&mut self) -> Result<(), E> {
+        self.sent = [None; MAX_DIGITS as usize];
+
+        // Make sure we are not in test mode
+        self.write(op::DISPLAYTEST, 0x00)?;
+        // Only scan the connected digits, which makes them brighter
+        self.write(op::SCANLIMIT, self.count - 1)?;
+        // Let the chip turn the digits into segments
+        self.write(op::DECODEMODE, DecodeMode::Decode7_0 as u8)?;
+        // Enable display
+        self.write(op::SHUTDOWN, 0x01)?;
+        self.write(op::INTENSITY, self.intensity)
+    }
+
+    /// Intensity from 0 to 15
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<(), E> {
+        self.intensity = intensity.min(0x0f);
+        self.write(op::INTENSITY, self.intensity)
+    }
+
+    /// Sends the digits that changed since the last flush
+    pub fn flush(&mut self) -> Result<(), E> {
+        for position in 0..self.count {
+            let value = self.digits[position as usize];
+            if self.sent[position as usize] == Some(value) {
+                continue;
+            }
+            self.write(op::DIGIT0 + position, value)?;
+            self.sent[position as usize] = Some(value);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, opcode: u8, data: u8) -> Result<(), E> {
+        self.spi.write(&[opcode, data])
+    }
+}
+
+impl<SPI> SevenSegment<SPI> {
+    /// Number of connected digits
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Blanks all digits
+    pub fn clear(&mut self) {
+        self.digits = [BLANK; MAX_DIGITS as usize];
+    }
+
+    /// Sets a single digit, characters Code-B cannot show are left blank
+    pub fn set_char(&mut self, position: u8, c: char, dot: bool) {
+        if position >= self.count {
+            return;
+        }
+        let value = code_b(c).unwrap_or(BLANK);
+        self.digits[position as usize] = if dot { value | DOT } else { value };
+    }
+
+    /// Writes the text so that its last character ends up at `position`.
+    /// A `.` lights the decimal point of the character before it.
+    pub fn write_str(&mut self, position: u8, text: &str) {
+        let mut position = position;
+        let mut dot = false;
+        for c in text.chars().rev() {
+            if c == '.' && !dot {
+                dot = true;
+                continue;
+            }
+            self.set_char(position, c, dot);
+            dot = false;
+            let Some(next) = position.checked_add(1) else {
+                return;
+            };
+            position = next;
+        }
+    }
+
+    /// Writes the number right aligned into `width` digits ending at `position`,
+    /// keeping only the lowest digits when it does not fit
+    pub fn write_number(&mut self, position: u8, width: u8, value: u32) {
+        let text = format!("{value:>width$}", width = width as usize);
+        let skip = text.len() - width as usize;
+        self.write_str(position, &text[skip..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::testing::SpiRecorder;
+
+    /// A display that was reset and flushed, with nothing recorded yet
+    fn flushed(count: u8) -> SevenSegment<SpiRecorder> {
+        let mut display = SevenSegment::new(SpiRecorder::default(), count);
+        display.reset().unwrap();
+        display.flush().unwrap();
+        display.spi.writes.clear();
+        display
+    }
+
+    #[test]
+    fn reset_scans_only_the_connected_digits() {
+        let mut display = SevenSegment::new(SpiRecorder::default(), 4);
+        display.reset().unwrap();
+        assert_eq!(
+            display.spi.writes,
+            [
+                [op::DISPLAYTEST, 0x00],
+                [op::SCANLIMIT, 3],
+                [op::DECODEMODE, 0xff],
+                [op::SHUTDOWN, 0x01],
+                [op::INTENSITY, 2],
+            ]
+        );
+    }
+
+    #[test]
+    fn dots_light_the_digit_before_them() {
+        let mut display = flushed(8);
+        display.write_str(0, "12.34");
+        assert_eq!(display.digits[..5], [4, 3, 2 | DOT, 1, BLANK]);
+
+        // A second dot gets a blank digit of its own, a leading one lights nothing
+        display.clear();
+        display.write_str(0, ".1..2");
+        assert_eq!(display.digits[..4], [2, BLANK | DOT, 1, BLANK]);
+    }
+
+    #[test]
+    fn text_is_cut_at_the_left_edge() {
+        let mut display = flushed(4);
+        display.write_str(2, "E12");
+        assert_eq!(display.digits[..4], [BLANK, BLANK, 2, 1]);
+
+        display.clear();
+        display.write_str(u8::MAX, "987");
+        assert!(display.digits.iter().all(|&digit| digit == BLANK));
+
+        display.write_number(0, 3, 12345);
+        assert_eq!(display.digits[..4], [5, 4, 3, BLANK]);
+    }
+
+    #[test]
+    fn flush_sends_only_changed_digits() {
+        let mut display = flushed(8);
+        display.write_number(0, 8, 7);
+        display.flush().unwrap();
+        assert_eq!(display.spi.writes, [[op::DIGIT0, 7]]);
+
+        display.spi.writes.clear();
+        display.flush().unwrap();
+        assert!(display.spi.writes.is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use std::convert::Infallible;
+
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::i2c::{self, I2c};
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiDevice};
+
+/// Records the bytes of every SPI transaction
+#[derive(Default)]
+pub struct SpiRecorder {
+    pub writes: Vec<Vec<u8>>,
+    /// Fails the next transaction instead
+    pub fail: bool,
+}
+
+impl ErrorType for SpiRecorder {
+    type Error = ErrorKind;
+}
+
+impl SpiDevice for SpiRecorder {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+        if std::mem::take(&mut self.fail) {
+            return Err(ErrorKind::Other);
+        }
+        for operation in operations {
+            if let Operation::Write(data) = operation {
+                self.writes.push(data.to_vec());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Records the address and the bytes written in every I2C transaction
+#[derive(Default)]
+pub struct I2cRecorder {
+    pub writes: Vec<(u8, Vec<u8>)>,
+}
+
+impl i2c::ErrorType for I2cRecorder {
+    type Error = i2c::ErrorKind;
+}
+
+impl I2c for I2cRecorder {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut bytes = Vec::new();
+        for operation in operations {
+            if let i2c::Operation::Write(data) = operation {
+                bytes.extend_from_slice(data);
+            }
+        }
+        self.writes.push((address, bytes));
+        Ok(())
+    }
+}
+
+/// Records every level an output pin is set to
+#[derive(Default)]
+pub struct PinRecorder {
+    pub levels: Vec<bool>,
+}
+
+impl digital::ErrorType for PinRecorder {
+    type Error = Infallible;
+}
+
+impl OutputPin for PinRecorder {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.levels.push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.levels.push(true);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for SpiRecorder {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+        SpiDevice::transaction(self, operations)
+    }
+}
+
+/// Runs a future that never has to wait, like the ones of the fakes here
+#[cfg(feature = "async")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
@@ -2,10 +2,17 @@ use std::fmt::Write;
 
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct TextDisplay {
     pub data: [u8; DISPLAY_HEIGHT as usize],
 }
 
+impl Default for TextDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TextDisplay {
     pub fn new() -> Self {
         Self {
@@ -23,7 +30,7 @@ impl TextDisplay {
 
         let line = self.data[y as usize];
         let mask = 0b1000_0000 >> x;
-        return line & mask == mask;
+        line & mask == mask
     }
 
     fn has_position(x: u8, y: u8) -> bool {
@@ -46,6 +53,12 @@ impl std::fmt::Display for TextDisplay {
 }
 
 impl super::Display for TextDisplay {
+    type Error = std::convert::Infallible;
+
+    fn size(&self) -> (u8, u8) {
+        (DISPLAY_WIDTH, DISPLAY_HEIGHT)
+    }
+
     fn fill(&mut self, value: bool) {
         self.data.fill(if value { 0xff } else { 0x00 });
     }
@@ -62,4 +75,18 @@ impl super::Display for TextDisplay {
             *line &= !mask;
         }
     }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        TextDisplay::get_pixel(self, x, y)
+    }
+
+    /// Nothing to send, the buffer is the display
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Text has no brightness
+    fn set_brightness(&mut self, _brightness: u8) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
@@ -0,0 +1,347 @@
+use embedded_hal::spi::SpiDevice;
+
+use super::ColorDisplay;
+use super::color::Rgb;
+use super::layout::ChainOrder;
+
+/// Current drawn by one colour channel at full brightness
+const MILLIAMPS_PER_CHANNEL: u32 = 20;
+
+/// Anything that sends a GRB byte stream to a strip, e.g. the RMT peripheral or SPI
+pub trait LedWriter {
+    type Error: std::fmt::Debug;
+
+    /// Sends three bytes per LED in green, red, blue order, starting with the first LED
+    fn write(&mut self, grb: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Keeps the last frame, e.g. to inspect it on the host
+impl LedWriter for Vec<u8> {
+    type Error = std::convert::Infallible;
+
+    fn write(&mut self, grb: &[u8]) -> Result<(), Self::Error> {
+        self.clear();
+        self.extend_from_slice(grb);
+        Ok(())
+    }
+}
+
+/// A matrix of WS2812 LEDs, e.g. an 8x32 WS2812B panel.
+///
+/// Lit pixels show the current colour, see [`ColorDisplay::set_color`]. Colours go through
+/// a gamma table and the brightness, and the whole frame is dimmed when it would
+/// draw more than the current limit.
+pub struct Ws2812<W> {
+    writer: W,
+    width: u8,
+    height: u8,
+    order: ChainOrder,
+    mirrored_x: bool,
+    mirrored_y: bool,
+    /// Colour of every LED, in chain order
+    pixels: Vec<Rgb>,
+    /// Colour for lit pixels
+    color: Rgb,
+    brightness: u8,
+    gamma: [u8; 256],
+    /// Upper limit for the current the LEDs draw, `None` for no limit
+    max_milliamps: Option<u32>,
+    /// The encoded frame
+    grb: Vec<u8>,
+}
+
+/// Lookup table from linear values to LED duty cycles
+fn gamma_table(gamma: f32) -> [u8; 256] {
+    let mut table = [0; 256];
+    for (value, entry) in table.iter_mut().enumerate() {
+        *entry = ((value as f32 / 255.0).powf(gamma) * 255.0).round() as u8;
+    }
+    table
+}
+
+impl<W: LedWriter> Ws2812<W> {
+    /// A `width` by `height` matrix with the LEDs wired in the given order,
+    /// starting top left. Lit pixels are white at a quarter of the full brightness.
+    pub fn new(writer: W, width: u8, height: u8, order: ChainOrder) -> Self {
+        let leds = width as usize * height as usize;
+        Self {
+            writer,
+            width,
+            height,
+            order,
+            mirrored_x: false,
+            mirrored_y: false,
+            pixels: vec![Rgb::BLACK; leds],
+            color: Rgb::WHITE,
+            brightness: 64,
+            gamma: gamma_table(2.2),
+            max_milliamps: None,
+            grb: Vec::with_capacity(leds * 3),
+        }
+    }
+
+    /// Flips the matrix left to right, e.g. when the strip starts top right
+    pub fn with_mirrored_x(mut self, mirrored: bool) -> Self {
+        self.mirrored_x = mirrored;
+        self
+    }
+
+    /// Flips the matrix upside down, e.g. when the strip starts bottom left
+    pub fn with_mirrored_y(mut self, mirrored: bool) -> Self {
+        self.mirrored_y = mirrored;
+        self
+    }
+
+    /// Gamma to correct the LEDs' brightness curve with, 1.0 turns correction off
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma_table(gamma);
+        self
+    }
+
+    /// Dims frames that would draw more than the given current, assuming
+    /// 20 mA per channel at full brightness
+    pub fn with_current_limit(mut self, milliamps: u32) -> Self {
+        self.max_milliamps = Some(milliamps);
+        self
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// The frame as last sent, three bytes per LED in green, red, blue order
+    pub fn grb(&self) -> &[u8] {
+        &self.grb
+    }
+
+    /// Position of a pixel in the strip
+    fn index(&self, x: u8, y: u8) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let x = if self.mirrored_x {
+            self.width - 1 - x
+        } else {
+            x
+        };
+        let y = if self.mirrored_y {
+            self.height - 1 - y
+        } else {
+            y
+        };
+        Some(self.order.index(
+            x as usize,
+            y as usize,
+            self.width as usize,
+            self.height as usize,
+        ))
+    }
+
+    /// Turns the pixels into the byte stream for the strip
+    fn encode(&mut self) {
+        self.grb.clear();
+        for pixel in &self.pixels {
+            let pixel = pixel.scale(self.brightness);
+            let gamma = |value: u8| self.gamma[value as usize];
+            self.grb
+                .extend_from_slice(&[gamma(pixel.g), gamma(pixel.r), gamma(pixel.b)]);
+        }
+
+        let Some(max_milliamps) = self.max_milliamps else {
+            return;
+        };
+        let total: u32 = self.grb.iter().map(|&value| value as u32).sum();
+        let milliamps = total * MILLIAMPS_PER_CHANNEL / 255;
+        if milliamps > max_milliamps {
+            for value in &mut self.grb {
+                *value = (*value as u32 * max_milliamps / milliamps) as u8;
+            }
+        }
+    }
+}
+
+impl<W: LedWriter> super::Display for Ws2812<W> {
+    type Error = W::Error;
+
+    fn size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+
+    fn fill(&mut self, value: bool) {
+        let color = if value { self.color } else { Rgb::BLACK };
+        self.pixels.fill(color);
+    }
+
+    fn set_pixel(&mut self, x: u8, y: u8, value: bool) {
+        let Some(index) = self.index(x, y) else {
+            return;
+        };
+        self.pixels[index] = if value { self.color } else { Rgb::BLACK };
+    }
+
+    fn get_pixel(&self, x: u8, y: u8) -> bool {
+        self.index(x, y)
+            .is_some_and(|index| !self.pixels[index].is_black())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.encode();
+        self.writer.write(&self.grb)
+    }
+
+    /// Scales all colours, applied before the gamma correction
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness = brightness;
+        Ok(())
+    }
+}
+
+impl<W: LedWriter> ColorDisplay for Ws2812<W> {
+    fn set_color(&mut self, color: Rgb) {
+        self.color = color;
+    }
+
+    fn set_color_pixel(&mut self, x: u8, y: u8, color: Rgb) {
+        let Some(index) = self.index(x, y) else {
+            return;
+        };
+        self.pixels[index] = color;
+    }
+
+    fn get_color_pixel(&self, x: u8, y: u8) -> Rgb {
+        self.index(x, y)
+            .map_or(Rgb::BLACK, |index| self.pixels[index])
+    }
+}
+
+/// Drives a strip from the MOSI pin of an SPI bus clocked at 3.2 MHz,
+/// where every bit for the LEDs becomes four bits on the bus
+pub struct Ws2812Spi<SPI> {
+    spi: SPI,
+    buffer: Vec<u8>,
+}
+
+/// Low time that latches the data, 280 µs for newer LEDs
+const RESET_BYTES: usize = 112;
+
+impl<SPI: SpiDevice> Ws2812Spi<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self {
+            spi,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl<SPI: SpiDevice> LedWriter for Ws2812Spi<SPI> {
+    type Error = SPI::Error;
+
+    fn write(&mut self, grb: &[u8]) -> Result<(), Self::Error> {
+        self.buffer.clear();
+        for &byte in grb {
+            // A short high pulse for 0, a long one for 1
+            let mut bits = 0u32;
+            for bit in (0..8).rev() {
+                let pattern = if byte & (1 << bit) != 0 {
+                    0b1110
+                } else {
+                    0b1000
+                };
+                bits = (bits << 4) | pattern;
+            }
+            self.buffer.extend_from_slice(&bits.to_be_bytes());
+        }
+        self.buffer.extend_from_slice(&[0; RESET_BYTES]);
+        self.spi.write(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::Display;
+
+    /// A matrix without gamma correction at full brightness, so bytes match the colours
+    fn linear(width: u8, height: u8, order: ChainOrder) -> Ws2812<Vec<u8>> {
+        let mut leds = Ws2812::new(Vec::new(), width, height, order).with_gamma(1.0);
+        leds.set_brightness(255).unwrap();
+        leds
+    }
+
+    /// Position in the chain of the only lit LED after lighting the given pixel
+    fn lit_index(order: ChainOrder, x: u8, y: u8) -> usize {
+        let mut leds = linear(4, 3, order);
+        leds.set_pixel(x, y, true);
+        leds.flush().unwrap();
+        let lit: Vec<usize> = leds
+            .writer()
+            .chunks(3)
+            .enumerate()
+            .filter(|(_, grb)| grb.iter().any(|&value| value != 0))
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(lit.len(), 1);
+        lit[0]
+    }
+
+    #[test]
+    fn serpentine_orders_reverse_every_second_line() {
+        assert_eq!(lit_index(ChainOrder::SerpentineRows, 1, 0), 1);
+        assert_eq!(lit_index(ChainOrder::SerpentineRows, 1, 1), 6);
+        assert_eq!(lit_index(ChainOrder::SerpentineRows, 0, 2), 8);
+        assert_eq!(lit_index(ChainOrder::SerpentineColumns, 0, 1), 1);
+        assert_eq!(lit_index(ChainOrder::SerpentineColumns, 1, 0), 5);
+        assert_eq!(lit_index(ChainOrder::SerpentineColumns, 2, 2), 8);
+    }
+
+    #[test]
+    fn mirroring_applies_before_the_chain_order() {
+        let mut leds = linear(4, 3, ChainOrder::Rows).with_mirrored_x(true);
+        leds.set_pixel(0, 1, true);
+        leds.flush().unwrap();
+        assert_eq!(leds.writer()[7 * 3..8 * 3], [255, 255, 255]);
+    }
+
+    #[test]
+    fn sends_green_red_blue() {
+        let mut leds = linear(2, 1, ChainOrder::Rows);
+        leds.set_color_pixel(0, 0, Rgb::new(10, 20, 30));
+        leds.set_color_pixel(1, 0, Rgb::new(255, 0, 0));
+        leds.flush().unwrap();
+        assert_eq!(leds.writer(), &[20, 10, 30, 0, 255, 0]);
+    }
+
+    #[test]
+    fn gamma_table_keeps_the_endpoints() {
+        for gamma in [1.0, 1.8, 2.2, 2.8] {
+            let table = gamma_table(gamma);
+            assert_eq!((table[0], table[255]), (0, 255));
+            assert!(table.is_sorted());
+        }
+        assert_eq!(gamma_table(2.2)[128], 56);
+        assert!(
+            gamma_table(1.0)
+                .iter()
+                .enumerate()
+                .all(|(i, &v)| i == v as usize)
+        );
+    }
+
+    #[test]
+    fn current_limit_dims_a_fully_lit_panel() {
+        let mut leds = linear(32, 8, ChainOrder::SerpentineColumns).with_current_limit(2000);
+        leds.fill(true);
+        leds.flush().unwrap();
+
+        // 256 LEDs with three channels at 20 mA would draw 15.36 A
+        assert!(leds.writer().iter().all(|&value| value == 33));
+        let total: u32 = leds.writer().iter().map(|&value| value as u32).sum();
+        assert!(total * MILLIAMPS_PER_CHANNEL / 255 <= 2000);
+
+        // A single LED stays below the limit and is left alone
+        leds.fill(false);
+        leds.set_pixel(0, 0, true);
+        leds.flush().unwrap();
+        assert_eq!(leds.writer()[..3], [255, 255, 255]);
+    }
+}
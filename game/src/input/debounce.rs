@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::{Button, InputEvent};
+
+/// How raw button edges are turned into clean presses and releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Debounce {
+    pub mode: DebounceMode,
+    pub window: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebounceMode {
+    /// Reports the first edge right away and ignores further edges of the same
+    /// button for the window. A level that changed meanwhile is reported afterwards.
+    Time,
+    /// Integrates how long the button was down or up and reports a change once
+    /// the level held for the window in total, so short glitches cancel out.
+    Integrator,
+}
+
+impl DebounceMode {
+    pub const ALL: [DebounceMode; 2] = [DebounceMode::Time, DebounceMode::Integrator];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            DebounceMode::Time => "time",
+            DebounceMode::Integrator => "integrator",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// Debounces every button on its own, so a press of one button never hides
+/// a press of another.
+///
+/// All timing comes from the events and the `now` passed to [`Debouncer::next`],
+/// so the debouncer can be driven with synthetic edges.
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    debounce: Debounce,
+    buttons: [ButtonState; Button::ALL.len()],
+    /// Debounced events in the order they happened
+    settled: VecDeque<InputEvent>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ButtonState {
+    /// Level reported by the last edge
+    raw: bool,
+    /// Time of the last edge
+    raw_since: Option<Instant>,
+    /// Level reported to the game
+    stable: bool,
+    /// Time mode: the next change is not reported before this time
+    blocked_until: Option<Instant>,
+    /// Integrator mode: time the button was down, between zero and the window
+    integral: Duration,
+    /// Integrator mode: time up to which `integral` is computed
+    integrated_until: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(debounce: Debounce) -> Self {
+        Self {
+            debounce,
+            buttons: [ButtonState::default(); Button::ALL.len()],
+            settled: VecDeque::new(),
+        }
+    }
+
+    /// Feeds an edge as reported by the hardware. Edges have to arrive in order.
+    pub fn feed(&mut self, event: InputEvent) {
+        // Changes that happened before this edge must not be lost
+        self.settle(event.time);
+
+        let window = self.debounce.window;
+        let state = &mut self.buttons[event.button.index()];
+        if self.debounce.mode == DebounceMode::Integrator {
+            let since = state.integrated_until.unwrap_or(event.time);
+            let elapsed = event.time.saturating_duration_since(since);
+            state.integral = if state.raw {
+                (state.integral + elapsed).min(window)
+            } else {
+                state.integral.saturating_sub(elapsed)
+            };
+            state.integrated_until = Some(event.time);
+        }
+        state.raw = event.pressed;
+        state.raw_since = Some(event.time);
+    }
+
+    /// Returns the next debounced event that happened until `now`
+    pub fn next(&mut self, now: Instant) -> Option<InputEvent> {
+        self.settle(now);
+        self.settled.pop_front()
+    }
+
+    /// Moves all changes that became stable until the given time to `settled`
+    fn settle(&mut self, until: Instant) {
+        let debounce = self.debounce;
+        let mut settled: Vec<InputEvent> = Button::ALL
+            .into_iter()
+            .filter_map(|button| {
+                let state = &mut self.buttons[button.index()];
+                let time = state.settle(debounce, until)?;
+                Some(InputEvent {
+                    button,
+                    pressed: state.stable,
+                    time,
+                })
+            })
+            .collect();
+
+        settled.sort_by_key(|event| event.time);
+        self.settled.extend(settled);
+    }
+}
+
+impl ButtonState {
+    /// Takes over the raw level if it is stable at the given time.
+    /// Returns when that happened.
+    fn settle(&mut self, debounce: Debounce, until: Instant) -> Option<Instant> {
+        if self.raw == self.stable {
+            return None;
+        }
+
+        let time = match debounce.mode {
+            DebounceMode::Time => {
+                let raw_since = self.raw_since?;
+                self.blocked_until
+                    .map_or(raw_since, |blocked| blocked.max(raw_since))
+            }
+            DebounceMode::Integrator => {
+                // Time until the integral reaches the other end at the current level
+                let remaining = if self.raw {
+                    debounce.window - self.integral
+                } else {
+                    self.integral
+                };
+                self.integrated_until? + remaining
+            }
+        };
+        if time > until {
+            return None;
+        }
+
+        self.stable = self.raw;
+        self.blocked_until = Some(time + debounce.window);
+        self.integral = if self.raw {
+            debounce.window
+        } else {
+            Duration::ZERO
+        };
+        self.integrated_until = Some(time);
+        Some(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::tests::replay;
+
+    /// The debounced edges until `until` milliseconds, in the same form as the raw ones
+    fn debounce(
+        mode: DebounceMode,
+        edges: &[(Button, bool, u64)],
+        until: u64,
+    ) -> Vec<(Button, bool, u64)> {
+        let mut debouncer = Debouncer::new(Debounce {
+            mode,
+            window: Duration::from_millis(30),
+        });
+        let next = |debouncer: &mut Debouncer, now| {
+            let event = debouncer.next(now)?;
+            Some(((event.button, event.pressed), event.time))
+        };
+        replay(&mut debouncer, edges, until, Debouncer::feed, next)
+            .into_iter()
+            .map(|((button, pressed), millis)| (button, pressed, millis))
+            .collect()
+    }
+
+    #[test]
+    fn time_mode_suppresses_bounces_inside_the_window() {
+        use Button::Rotate;
+        let edges = [
+            // A bouncing press
+            (Rotate, true, 0),
+            (Rotate, false, 2),
+            (Rotate, true, 4),
+            (Rotate, false, 6),
+            (Rotate, true, 8),
+            // A bouncing release
+            (Rotate, false, 100),
+            (Rotate, true, 102),
+            (Rotate, false, 104),
+        ];
+        assert_eq!(
+            debounce(DebounceMode::Time, &edges, 200),
+            [(Rotate, true, 0), (Rotate, false, 100)]
+        );
+    }
+
+    #[test]
+    fn time_mode_reports_a_level_that_changed_during_the_window() {
+        use Button::Down;
+        let edges = [(Down, true, 0), (Down, false, 10)];
+        assert_eq!(debounce(DebounceMode::Time, &edges, 20), [(Down, true, 0)]);
+        assert_eq!(
+            debounce(DebounceMode::Time, &edges, 30),
+            [(Down, true, 0), (Down, false, 30)]
+        );
+    }
+
+    #[test]
+    fn integrator_mode_needs_the_level_for_the_whole_window() {
+        use Button::Left;
+
+        // A glitch shorter than the window is never reported
+        let glitch = [(Left, true, 0), (Left, false, 10)];
+        assert_eq!(debounce(DebounceMode::Integrator, &glitch, 100), []);
+
+        // A press is reported once it was held for the window
+        let press = [(Left, true, 0)];
+        assert_eq!(debounce(DebounceMode::Integrator, &press, 29), []);
+        assert_eq!(
+            debounce(DebounceMode::Integrator, &press, 30),
+            [(Left, true, 30)]
+        );
+
+        // A short bounce only takes away the time the contact was open
+        let bounce = [(Left, true, 0), (Left, false, 20), (Left, true, 25)];
+        assert_eq!(
+            debounce(DebounceMode::Integrator, &bounce, 100),
+            [(Left, true, 40)]
+        );
+    }
+
+    #[test]
+    fn buttons_are_debounced_independently() {
+        use Button::{Left, Rotate};
+        let edges = [
+            (Left, true, 0),
+            (Left, false, 20),
+            // Inside the window of the left button
+            (Rotate, true, 25),
+            (Rotate, false, 60),
+        ];
+        assert_eq!(
+            debounce(DebounceMode::Time, &edges, 200),
+            [
+                (Left, true, 0),
+                (Rotate, true, 25),
+                (Left, false, 30),
+                (Rotate, false, 60),
+            ]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::Button;
+use crate::logic::ButtonAction;
+
+/// How long a button has to be held to count as a long press
+pub const LONG_PRESS: Duration = Duration::from_millis(500);
+/// How close together two presses have to be to count as a chord
+pub const CHORD_WINDOW: Duration = Duration::from_millis(60);
+
+/// Something the player does with the buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Press(Button),
+    /// Holding a button for [`LONG_PRESS`]
+    LongPress(Button),
+    /// Pressing two buttons within [`CHORD_WINDOW`], in any order
+    Chord(Button, Button),
+}
+
+impl Trigger {
+    /// Chords are stored with the buttons in a fixed order, so lookups do not
+    /// depend on which button went down first
+    pub fn chord(a: Button, b: Button) -> Self {
+        if a.index() <= b.index() {
+            Trigger::Chord(a, b)
+        } else {
+            Trigger::Chord(b, a)
+        }
+    }
+
+    fn serialize(self) -> String {
+        match self {
+            Trigger::Press(button) => button.name().to_string(),
+            Trigger::LongPress(button) => format!("long {}", button.name()),
+            Trigger::Chord(a, b) => format!("{}+{}", a.name(), b.name()),
+        }
+    }
+
+    fn deserialize(string: &str) -> Option<Self> {
+        if let Some(name) = string.strip_prefix("long ") {
+            return Some(Trigger::LongPress(Button::from_name(name.trim())?));
+        }
+        if let Some((a, b)) = string.split_once('+') {
+            let (a, b) = (Button::from_name(a.trim())?, Button::from_name(b.trim())?);
+            return (a != b).then(|| Trigger::chord(a, b));
+        }
+        Some(Trigger::Press(Button::from_name(string)?))
+    }
+}
+
+/// Binds triggers to the actions they perform.
+///
+/// A key map is written as one `trigger=action` line per binding, e.g.
+///
+/// ```text
+/// left=move_left
+/// long rotate=hold
+/// left+right=pause
+/// ```
+///
+/// Buttons that are part of a chord report their own press only after
+/// [`CHORD_WINDOW`], buttons with a long press only once they are released.
+/// The default map therefore only binds plain presses, pause and hold have
+/// to be bound explicitly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    pub bindings: Vec<(Trigger, ButtonAction)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Trigger::Press(Button::Left), ButtonAction::MoveLeft),
+                (Trigger::Press(Button::Right), ButtonAction::MoveRight),
+                (Trigger::Press(Button::Down), ButtonAction::MoveDown),
+                (Trigger::Press(Button::Rotate), ButtonAction::Rotate),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    pub fn action(&self, trigger: Trigger) -> Option<ButtonAction> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == trigger)
+            .map(|&(_, action)| action)
+    }
+
+    pub(crate) fn has_long_press(&self, button: Button) -> bool {
+        self.action(Trigger::LongPress(button)).is_some()
+    }
+
+    pub(crate) fn has_chord(&self, button: Button) -> bool {
+        self.bindings.iter().any(
+            |(trigger, _)| matches!(*trigger, Trigger::Chord(a, b) if a == button || b == button),
+        )
+    }
+
+    pub fn serialize(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|(trigger, action)| format!("{}={}\n", trigger.serialize(), action.name()))
+            .collect()
+    }
+
+    /// Parses `trigger=action` lines as written by [`KeyMap::serialize`].
+    /// Empty lines and lines starting with `#` are ignored. Moving and rotating
+    /// have to be bound to plain presses, otherwise the game could not be played.
+    pub fn deserialize(string: &str) -> Result<Self, ParseKeyMapError> {
+        let mut bindings: Vec<(Trigger, ButtonAction)> = Vec::new();
+
+        for line in string
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let (trigger, action) = line
+                .split_once('=')
+                .ok_or_else(|| ParseKeyMapError::MissingSeparator(line.to_string()))?;
+            let trigger = Trigger::deserialize(trigger.trim())
+                .ok_or_else(|| ParseKeyMapError::InvalidTrigger(trigger.trim().to_string()))?;
+            let action = ButtonAction::from_name(action.trim())
+                .ok_or_else(|| ParseKeyMapError::InvalidAction(action.trim().to_string()))?;
+
+            if bindings.iter().any(|(bound, _)| *bound == trigger) {
+                return Err(ParseKeyMapError::DuplicateTrigger(trigger.serialize()));
+            }
+            bindings.push((trigger, action));
+        }
+
+        // Without these the buttons could not even start a game to fix the map
+        for required in [
+            ButtonAction::MoveLeft,
+            ButtonAction::MoveRight,
+            ButtonAction::MoveDown,
+            ButtonAction::Rotate,
+        ] {
+            let pressed = bindings.iter().any(|&(trigger, action)| {
+                matches!(trigger, Trigger::Press(_)) && action == required
+            });
+            if !pressed {
+                return Err(ParseKeyMapError::MissingPress(required.name().to_string()));
+            }
+        }
+
+        Ok(Self { bindings })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseKeyMapError {
+    /// A line without `=`
+    MissingSeparator(String),
+    /// Neither a button name, `long <button>` nor `<button>+<button>`
+    InvalidTrigger(String),
+    /// Not the name of a [`ButtonAction`]
+    InvalidAction(String),
+    /// The same trigger is bound twice
+    DuplicateTrigger(String),
+    /// An action needed to play is not bound to a plain button press
+    MissingPress(String),
+}
+
+impl fmt::Display for ParseKeyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyMapError::MissingSeparator(line) => write!(f, "missing '=' in line '{line}'"),
+            ParseKeyMapError::InvalidTrigger(trigger) => write!(f, "invalid trigger '{trigger}'"),
+            ParseKeyMapError::InvalidAction(action) => write!(f, "invalid action '{action}'"),
+            ParseKeyMapError::DuplicateTrigger(trigger) => {
+                write!(f, "'{trigger}' is bound more than once")
+            }
+            ParseKeyMapError::MissingPress(action) => {
+                write!(f, "'{action}' has to be bound to a button press")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseKeyMapError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_default() {
+        let keymap = KeyMap::default();
+        assert_eq!(KeyMap::deserialize(&keymap.serialize()), Ok(keymap));
+    }
+
+    #[test]
+    fn requires_a_press_for_every_move() {
+        assert_eq!(
+            KeyMap::deserialize(""),
+            Err(ParseKeyMapError::MissingPress("move_left".to_string()))
+        );
+
+        let without_rotate =
+            "left=move_left\nright=move_right\ndown=move_down\nlong rotate=rotate\n";
+        assert_eq!(
+            KeyMap::deserialize(without_rotate),
+            Err(ParseKeyMapError::MissingPress("rotate".to_string()))
+        );
+
+        let remapped =
+            "rotate=move_left\nright=move_right\ndown=move_down\nleft=rotate\nleft+right=pause\n";
+        assert!(KeyMap::deserialize(remapped).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use std::time::Instant;
+
+use super::{Button, InputEvent, InputSource};
+
+/// Wraps an [`InputSource`] that can lose edges, e.g. because an interrupt is
+/// still disabled from the previous edge, and corrects it with the actual
+/// level of the buttons.
+///
+/// Events that repeat the level of the previous one are dropped, so a button
+/// is never pressed twice without a release in between.
+pub struct LevelCheck<S> {
+    source: S,
+    /// Level of each button as last handed out
+    pressed: [bool; Button::ALL.len()],
+    /// Levels read from the buttons and when, compared once the events before are handed out
+    sampled: Option<([bool; Button::ALL.len()], Instant)>,
+    /// Event taken from the source that happened after the sampled levels
+    peeked: Option<InputEvent>,
+}
+
+impl<S: InputSource> LevelCheck<S> {
+    /// Assumes all buttons are released
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            pressed: [false; Button::ALL.len()],
+            sampled: None,
+            peeked: None,
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Levels of all buttons in the order of [`Button::ALL`], read at `time`.
+    /// Buttons that differ from the last event get an event at that time.
+    pub fn sample(&mut self, pressed: [bool; Button::ALL.len()], time: Instant) {
+        self.sampled = Some((pressed, time));
+    }
+}
+
+impl<S: InputSource> InputSource for LevelCheck<S> {
+    fn poll(&mut self) -> Option<InputEvent> {
+        loop {
+            let event = self.peeked.take().or_else(|| self.source.poll());
+
+            if let Some((sampled, time)) = self.sampled
+                && event.is_none_or(|event| event.time > time)
+            {
+                self.peeked = event;
+                let lost = Button::ALL
+                    .into_iter()
+                    .find(|button| sampled[button.index()] != self.pressed[button.index()]);
+                let Some(button) = lost else {
+                    self.sampled = None;
+                    continue;
+                };
+                self.pressed[button.index()] = sampled[button.index()];
+                return Some(InputEvent {
+                    button,
+                    pressed: sampled[button.index()],
+                    time,
+                });
+            }
+
+            let event = event?;
+            if event.pressed != self.pressed[event.button.index()] {
+                self.pressed[event.button.index()] = event.pressed;
+                return Some(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn events(start: Instant, edges: &[(Button, bool, u64)]) -> VecDeque<InputEvent> {
+        crate::input::tests::events(start, edges).collect()
+    }
+
+    #[test]
+    fn releases_a_button_whose_release_was_lost() {
+        let start = Instant::now();
+        let mut input = LevelCheck::new(events(start, &[(Button::Left, true, 0)]));
+        input.sample([false; 4], start + Duration::from_millis(20));
+
+        let polled: VecDeque<_> = std::iter::from_fn(|| input.poll()).collect();
+        assert_eq!(
+            polled,
+            events(start, &[(Button::Left, true, 0), (Button::Left, false, 20)])
+        );
+    }
+
+    #[test]
+    fn keeps_the_order_of_later_edges() {
+        use Button::{Down, Left, Rotate};
+
+        // The press of Down was lost, the release of Rotate was recorded twice
+        let start = Instant::now();
+        let edges = [
+            (Rotate, true, 0),
+            (Rotate, false, 10),
+            (Rotate, false, 12),
+            (Left, true, 40),
+        ];
+        let mut input = LevelCheck::new(events(start, &edges));
+        input.sample(
+            [false, false, true, false],
+            start + Duration::from_millis(30),
+        );
+
+        let polled: VecDeque<_> = std::iter::from_fn(|| input.poll()).collect();
+        assert_eq!(
+            polled,
+            events(
+                start,
+                &[
+                    (Rotate, true, 0),
+                    (Rotate, false, 10),
+                    (Down, true, 30),
+                    (Left, true, 40),
+                ]
+            )
+        );
+    }
+}
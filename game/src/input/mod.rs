@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::logic::{ButtonAction, GameEvent, GameState};
+
+mod debounce;
+pub use debounce::{Debounce, DebounceMode, Debouncer};
+
+mod keymap;
+pub use keymap::{CHORD_WINDOW, KeyMap, LONG_PRESS, ParseKeyMapError, Trigger};
+
+mod level_check;
+pub use level_check::LevelCheck;
+
+mod queue;
+pub use queue::EventQueue;
+
+/// The physical buttons of the console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Down,
+    Rotate,
+}
+
+impl Button {
+    pub const ALL: [Button; 4] = [Button::Left, Button::Right, Button::Down, Button::Rotate];
+
+    const fn index(self) -> usize {
+        match self {
+            Button::Left => 0,
+            Button::Right => 1,
+            Button::Down => 2,
+            Button::Rotate => 3,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Button::Left => "left",
+            Button::Right => "right",
+            Button::Down => "down",
+            Button::Rotate => "rotate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|button| button.name() == name)
+    }
+}
+
+/// A button changing its state at a given time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub button: Button,
+    /// `true` when the button went down, `false` when it was released
+    pub pressed: bool,
+    pub time: Instant,
+}
+
+/// Anything that delivers button events, e.g. interrupt handlers on the device
+/// or a script on the host
+pub trait InputSource {
+    /// Returns the oldest event that was not handed out yet
+    fn poll(&mut self) -> Option<InputEvent>;
+}
+
+/// Scripted input, e.g. for the simulator
+impl InputSource for VecDeque<InputEvent> {
+    fn poll(&mut self) -> Option<InputEvent> {
+        self.pop_front()
+    }
+}
+
+/// Turns raw button edges into game actions according to a [`KeyMap`]
+/// and keeps track of held buttons
+#[derive(Debug, Clone)]
+pub struct Controls {
+    debouncer: Debouncer,
+    keymap: KeyMap,
+    held: [bool; Button::ALL.len()],
+    /// Presses waiting for a chord partner, a long press or the release
+    pending: [Option<Instant>; Button::ALL.len()],
+    /// Actions that were triggered but not handed out yet
+    actions: VecDeque<(ButtonAction, Instant)>,
+}
+
+impl Controls {
+    pub fn new(debounce: Debounce, keymap: KeyMap) -> Self {
+        Self {
+            debouncer: Debouncer::new(debounce),
+            keymap,
+            held: [false; Button::ALL.len()],
+            pending: [None; Button::ALL.len()],
+            actions: VecDeque::new(),
+        }
+    }
+
+    pub fn keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    /// Replaces the key map, presses that are still undecided are dropped
+    pub fn set_keymap(&mut self, keymap: KeyMap) {
+        self.keymap = keymap;
+        self.pending = [None; Button::ALL.len()];
+    }
+
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held[button.index()]
+    }
+
+    /// Feeds an edge as reported by the hardware
+    pub fn feed(&mut self, event: InputEvent) {
+        self.debouncer.feed(event);
+    }
+
+    /// Returns the next action triggered until `now`, along with when it happened
+    pub fn next_action(&mut self, now: Instant) -> Option<(ButtonAction, Instant)> {
+        loop {
+            if let Some(action) = self.actions.pop_front() {
+                return Some(action);
+            }
+
+            let Some(event) = self.debouncer.next(now) else {
+                self.expire(now);
+                return self.actions.pop_front();
+            };
+            self.expire(event.time);
+            self.handle(event);
+        }
+    }
+
+    /// Decides presses whose chord window or long press time ran out until the given time
+    fn expire(&mut self, until: Instant) {
+        let mut expired = Vec::new();
+
+        for button in Button::ALL {
+            let Some(pressed_at) = self.pending[button.index()] else {
+                continue;
+            };
+
+            let (trigger, time) = if self.keymap.has_long_press(button) {
+                (Trigger::LongPress(button), pressed_at + LONG_PRESS)
+            } else {
+                // No chord partner showed up in time
+                (Trigger::Press(button), pressed_at + CHORD_WINDOW)
+            };
+            if time > until {
+                continue;
+            }
+
+            self.pending[button.index()] = None;
+            if let Some(action) = self.keymap.action(trigger) {
+                expired.push((action, time));
+            }
+        }
+
+        expired.sort_by_key(|&(_, time)| time);
+        self.actions.extend(expired);
+    }
+
+    fn handle(&mut self, event: InputEvent) {
+        let button = event.button;
+        self.held[button.index()] = event.pressed;
+
+        if !event.pressed {
+            // A short press of a button that waited for a long press or a chord
+            if self.pending[button.index()].take().is_some()
+                && let Some(action) = self.keymap.action(Trigger::Press(button))
+            {
+                self.actions.push_back((action, event.time));
+            }
+            return;
+        }
+
+        // Second button of a chord, which replaces the presses of both buttons
+        let chord = Button::ALL.into_iter().find_map(|other| {
+            let pressed_at = self.pending[other.index()]?;
+            if other == button || event.time.duration_since(pressed_at) > CHORD_WINDOW {
+                return None;
+            }
+            Some((other, self.keymap.action(Trigger::chord(button, other))?))
+        });
+        if let Some((other, action)) = chord {
+            self.pending[other.index()] = None;
+            self.actions.push_back((action, event.time));
+            return;
+        }
+
+        if self.keymap.has_long_press(button) || self.keymap.has_chord(button) {
+            self.pending[button.index()] = Some(event.time);
+        } else if let Some(action) = self.keymap.action(Trigger::Press(button)) {
+            self.actions.push_back((action, event.time));
+        }
+    }
+}
+
+impl GameState {
+    /// Feeds every pending input event into the game in the order they happened,
+    /// then advances the game to `now`
+    pub fn update_from(
+        self,
+        input: &mut impl InputSource,
+        controls: &mut Controls,
+        now: Instant,
+        events: &mut Vec<GameEvent>,
+    ) -> Self {
+        while let Some(event) = input.poll() {
+            controls.feed(event);
+        }
+
+        let mut game_state = self;
+        while let Some((action, time)) = controls.next_action(now) {
+            game_state = game_state.update(Some(action), time, events);
+        }
+
+        game_state.update(None, now, events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Turns `(button, pressed, milliseconds)` edges into events, counting from `start`
+    pub(crate) fn events(
+        start: Instant,
+        edges: &[(Button, bool, u64)],
+    ) -> impl Iterator<Item = InputEvent> {
+        edges
+            .iter()
+            .map(move |&(button, pressed, millis)| InputEvent {
+                button,
+                pressed,
+                time: start + Duration::from_millis(millis),
+            })
+    }
+
+    /// Feeds `(button, pressed, milliseconds)` edges into `state`, then collects
+    /// what `next` hands out until `until` milliseconds, along with the milliseconds
+    /// it happened at
+    pub(crate) fn replay<S, T>(
+        state: &mut S,
+        edges: &[(Button, bool, u64)],
+        until: u64,
+        feed: impl Fn(&mut S, InputEvent),
+        next: impl Fn(&mut S, Instant) -> Option<(T, Instant)>,
+    ) -> Vec<(T, u64)> {
+        let start = Instant::now();
+        for event in events(start, edges) {
+            feed(state, event);
+        }
+        let until = start + Duration::from_millis(until);
+        std::iter::from_fn(|| next(state, until))
+            .map(|(item, time)| (item, time.duration_since(start).as_millis() as u64))
+            .collect()
+    }
+
+    /// The actions triggered by the edges until `until` milliseconds
+    fn actions(
+        keymap: KeyMap,
+        edges: &[(Button, bool, u64)],
+        until: u64,
+    ) -> Vec<(ButtonAction, u64)> {
+        let debounce = Debounce {
+            mode: DebounceMode::Time,
+            window: Duration::from_millis(5),
+        };
+        let mut controls = Controls::new(debounce, keymap);
+        replay(
+            &mut controls,
+            edges,
+            until,
+            Controls::feed,
+            Controls::next_action,
+        )
+    }
+
+    #[test]
+    fn default_map_reports_presses_right_away() {
+        let edges = [
+            (Button::Left, true, 0),
+            (Button::Left, false, 20),
+            (Button::Rotate, true, 30),
+            (Button::Down, true, 40),
+        ];
+        assert_eq!(
+            actions(KeyMap::default(), &edges, 40),
+            [
+                (ButtonAction::MoveLeft, 0),
+                (ButtonAction::Rotate, 30),
+                (ButtonAction::MoveDown, 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn chords_delay_the_presses_of_their_buttons() {
+        let mut keymap = KeyMap::default();
+        keymap.bindings.push((
+            Trigger::chord(Button::Left, Button::Right),
+            ButtonAction::Pause,
+        ));
+
+        let edges = [
+            (Button::Left, true, 0),
+            (Button::Rotate, true, 10),
+            (Button::Left, false, 80),
+            (Button::Left, true, 100),
+            (Button::Right, true, 130),
+        ];
+        let window = CHORD_WINDOW.as_millis() as u64;
+        assert_eq!(
+            actions(keymap, &edges, 200),
+            [
+                (ButtonAction::Rotate, 10),
+                (ButtonAction::MoveLeft, window),
+                (ButtonAction::Pause, 130),
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Lock-free ring buffer for a single producer, e.g. an interrupt handler,
+/// and a single consumer, e.g. the main loop.
+///
+/// It can be placed in a `static` and holds up to `N - 1` elements, `N` has to be at least 2.
+pub struct EventQueue<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    /// Next slot to read, only written by the consumer
+    head: AtomicUsize,
+    /// Next slot to write, only written by the producer
+    tail: AtomicUsize,
+}
+
+// Elements are handed from the producer to the consumer, which may be on another thread
+unsafe impl<T: Send, const N: usize> Sync for EventQueue<T, N> {}
+
+impl<T, const N: usize> EventQueue<T, N> {
+    pub const fn new() -> Self {
+        // One slot always stays free to tell a full queue from an empty one
+        const { assert!(N >= 2, "an event queue needs at least two slots") };
+        Self {
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Appends an element, or hands it back when the queue is full.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while another call to `push` is running,
+    /// i.e. there may only be one producer at a time.
+    pub unsafe fn push(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % N;
+        if next == self.head.load(Ordering::Acquire) {
+            return Err(value);
+        }
+
+        // The consumer does not touch the slot until the new tail is published
+        unsafe { (*self.slots[tail].get()).write(value) };
+        self.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    /// Removes the oldest element.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while another call to `pop` is running,
+    /// i.e. there may only be one consumer at a time.
+    pub unsafe fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        // The producer does not touch the slot until the new head is published
+        let value = unsafe { (*self.slots[head].get()).assume_init_read() };
+        self.head.store((head + 1) % N, Ordering::Release);
+        Some(value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+}
+
+impl<T, const N: usize> Default for EventQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for EventQueue<T, N> {
+    fn drop(&mut self) {
+        // Exclusive access, so there is no other producer or consumer
+        while unsafe { self.pop() }.is_some() {}
+    }
+}
@@ -1,5 +1,8 @@
 pub mod display;
+pub mod input;
 pub mod logic;
+pub mod scheduler;
+pub mod simulator;
 
 pub const DISPLAY_WIDTH: u8 = 8;
 pub const DISPLAY_HEIGHT: u8 = 8 * 4;
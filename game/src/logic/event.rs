@@ -0,0 +1,41 @@
+use super::GameMode;
+
+/// Something noteworthy that happened while updating the [`super::GameState`].
+///
+/// Events are collected by [`super::GameState::update`] so that sound, telemetry,
+/// highscore saving or animations can react to them without the game logic
+/// knowing about any of these subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A new game was started from the start menu
+    GameStarted,
+    /// A new piece became the current piece
+    PieceSpawned,
+    /// The current piece was placed on top of the existing blocks
+    PieceLocked,
+    /// Full rows were removed from the board
+    LinesCleared {
+        count: u32,
+        /// Indices of the removed rows before the rows above collapsed
+        rows: Vec<u8>,
+    },
+    /// Enough lines were cleared to reach the next level
+    LevelUp { level: u32 },
+    /// A puzzle was solved or can no longer be solved, followed by [`GameEvent::GameOver`]
+    PuzzleFinished { solved: bool },
+    /// The game ended
+    GameOver { stats: GameStats },
+    /// The score of the game that just ended was entered into the highscore
+    /// table at the given position, starting at 0
+    NewHighscore { rank: usize },
+}
+
+/// Summary of a game, handed out with [`GameEvent::GameOver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameStats {
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub pieces: u32,
+    pub mode: GameMode,
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+
+const MAX_HIGHSCORES: usize = 10;
+
+/// Initials of entries stored before names could be entered
+const ANONYMOUS: [char; 3] = ['-', '-', '-'];
+
+/// The best scores of regular games, highest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highscores {
+    pub entries: Vec<Highscore>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highscore {
+    /// Three letters entered by the player, arcade style
+    pub initials: [char; 3],
+    pub score: u32,
+}
+
+impl Highscores {
+    /// Whether the score would make it into the table, games without any points never do
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGHSCORES
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Inserts the score and returns its position in the table, starting at 0,
+    /// or `None` when it did not make it into the table
+    pub fn add_score(&mut self, initials: [char; 3], new_score: u32) -> Option<usize> {
+        if !self.qualifies(new_score) {
+            return None;
+        }
+
+        // Equal scores keep their order, the newer one comes last
+        let rank = self
+            .entries
+            .partition_point(|entry| entry.score >= new_score);
+        self.entries.insert(
+            rank,
+            Highscore {
+                initials,
+                score: new_score,
+            },
+        );
+        self.entries.truncate(MAX_HIGHSCORES);
+        Some(rank)
+    }
+
+    /// Writes the entries as comma separated `ABC:123` pairs
+    pub fn serialize(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}:{}", String::from_iter(entry.initials), entry.score))
+            .reduce(|accum, elem| accum + "," + &elem)
+            .unwrap_or_default()
+    }
+
+    /// Parses the output of [`Highscores::serialize`]. Bare scores without
+    /// initials, as stored by earlier versions, are read as anonymous entries.
+    pub fn deserialize(string: &str) -> Result<Self, ParseHighscoresError> {
+        Ok(Self {
+            entries: string.split(",").map(Highscore::parse).try_fold(
+                Vec::new(),
+                |mut accum, maybe_elem| {
+                    accum.push(maybe_elem?);
+                    Ok(accum)
+                },
+            )?,
+        })
+    }
+}
+
+impl Highscore {
+    fn parse(string: &str) -> Result<Self, ParseHighscoresError> {
+        let (initials, score) = match string.split_once(':') {
+            Some((initials, score)) => {
+                let initials: Vec<char> = initials.chars().collect();
+                let initials = initials
+                    .try_into()
+                    .map_err(|_| ParseHighscoresError(string.to_string()))?;
+                (initials, score)
+            }
+            None => (ANONYMOUS, string),
+        };
+
+        Ok(Self {
+            initials,
+            score: score
+                .parse()
+                .map_err(|_| ParseHighscoresError(string.to_string()))?,
+        })
+    }
+}
+
+impl Default for Highscores {
+    fn default() -> Self {
+        Highscores {
+            entries: Vec::with_capacity(MAX_HIGHSCORES),
+        }
+    }
+}
+
+/// An entry that is neither `ABC:123` nor a bare score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHighscoresError(String);
+
+impl fmt::Display for ParseHighscoresError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid highscore entry '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseHighscoresError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(scores: &[u32]) -> Highscores {
+        Highscores {
+            entries: scores
+                .iter()
+                .map(|&score| Highscore {
+                    initials: ANONYMOUS,
+                    score,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn zero_never_qualifies() {
+        let mut highscores = table(&[]);
+        assert!(!highscores.qualifies(0));
+        assert_eq!(highscores.add_score(ANONYMOUS, 0), None);
+        assert!(highscores.qualifies(1));
+    }
+
+    #[test]
+    fn full_table_needs_a_better_score() {
+        let highscores = table(&[100, 90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        assert!(!highscores.qualifies(10));
+        assert!(highscores.qualifies(11));
+        assert!(table(&[100]).qualifies(5));
+    }
+}
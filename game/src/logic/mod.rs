@@ -1,77 +1,534 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 use crate::display::render::wrap_x;
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
 pub mod piece;
-use piece::{Piece, Rotation};
+use piece::{Piece, PieceKind, Rotation};
+
+pub mod puzzle;
+use puzzle::{Goal, bundled_puzzles};
+
+mod event;
+pub use event::{GameEvent, GameStats};
+
+mod highscore;
+pub use highscore::{Highscore, Highscores, ParseHighscoresError};
+
+mod rules;
+pub use rules::{ParseRuleSetError, Preset, RuleSet};
 
 pub enum GameState {
     StartMenu(InStartState),
     InGame(InGameState),
-    GameOver(u32),
+    GameOver(InGameOverState),
+    HighscoreEntry(InHighscoreEntryState),
 }
 
+/// Time between two animation steps of the start menu, e.g. scrolling text by one row
+const MENU_TICK_INTERVAL: Duration = Duration::from_millis(60);
+/// Highest level that can be selected to start on
+const MAX_START_LEVEL: u32 = 9;
+
 pub struct InStartState {
-    pub phase: StartMenuPhase,
+    pub item: MenuItem,
+    /// Whether the highscore table is shown instead of the selected item
+    pub showing_highscores: bool,
+    /// Animation steps since the shown item changed, drives scrolling text
+    pub ticks: u32,
     pub last_update: Instant,
+    pub rules: RuleSet,
+    pub mode: GameMode,
+    pub(crate) highscores: Highscores,
+    rng: SmallRng,
+}
+
+/// What kind of game is started from the start menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Endless game with random pieces
+    Marathon,
+    /// One of the [`bundled_puzzles`], by index
+    Puzzle(usize),
+}
+
+impl GameMode {
+    /// The mode after this one, cycling through the puzzles that fit below
+    /// the preview region of the spawn zone
+    pub fn next(self, spawn_zone: &SpawnZone) -> Self {
+        let start = match self {
+            GameMode::Marathon => 0,
+            GameMode::Puzzle(index) => index.saturating_add(1),
+        };
+        (start..bundled_puzzles().len())
+            .find(|&index| bundled_puzzles()[index].fits(spawn_zone))
+            .map_or(GameMode::Marathon, GameMode::Puzzle)
+    }
+
+    /// The mode before this one, cycling through the puzzles that fit below
+    /// the preview region of the spawn zone
+    pub fn previous(self, spawn_zone: &SpawnZone) -> Self {
+        let end = match self {
+            GameMode::Marathon => bundled_puzzles().len(),
+            GameMode::Puzzle(index) => index.min(bundled_puzzles().len()),
+        };
+        (0..end)
+            .rev()
+            .find(|&index| bundled_puzzles()[index].fits(spawn_zone))
+            .map_or(GameMode::Marathon, GameMode::Puzzle)
+    }
+}
+
+/// Time for the game over curtain to fill or clear one row
+const CURTAIN_ROW_INTERVAL: Duration = Duration::from_millis(25);
+/// How long the score is shown before a button press returns to the start menu
+const MIN_SCORE_DURATION: Duration = Duration::from_millis(1000);
+/// Blink interval of the new highscore indication
+const HIGHLIGHT_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct InGameOverState {
+    pub score: u32,
+    /// Whether the score made it into the highscore table
+    pub new_highscore: bool,
+    pub animation: GameOverAnimation,
+    /// The board as it was when the game ended
+    pub(crate) blocks: Blocks,
+    started: Instant,
+    highscores: Highscores,
+    rules: RuleSet,
+    mode: GameMode,
+    rng: SmallRng,
+}
+
+/// Blink interval of the cursor below the initial being edited
+const CURSOR_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Arcade style entry of three initials after a score made the highscore table
+pub struct InHighscoreEntryState {
+    pub score: u32,
+    pub initials: [char; 3],
+    /// Index of the initial being edited
+    pub position: usize,
+    /// Blinks to show which initial is being edited
+    pub cursor_visible: bool,
+    last_update: Instant,
+    highscores: Highscores,
+    rules: RuleSet,
+    mode: GameMode,
+    rng: SmallRng,
+}
+
+/// Sequence shown after a game ended, before the start menu is reachable again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverAnimation {
+    /// The given number of rows is filled, from the bottom up
+    Fill(u8),
+    /// The given number of rows is cleared again, from the bottom up
+    Clear(u8),
+    /// The score is shown, `highlight` blinks for a new highscore
+    Score { highlight: bool },
+}
+
+/// Entries of the start menu, cycled with left and right and activated with rotate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    /// Starts a game with the selected settings
+    Play,
+    /// Cycles through the [`GameMode`]s
+    Mode,
+    /// Cycles through the levels a game can start on, only shown when
+    /// gravity gets faster on higher levels
+    Level,
+    /// Toggles [`RuleSet::wrap`]
+    Wrap,
+    /// Shows the highscore table
+    Highscores,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 5] = [
+        MenuItem::Play,
+        MenuItem::Mode,
+        MenuItem::Level,
+        MenuItem::Wrap,
+        MenuItem::Highscores,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&item| item == self).unwrap()
+    }
+
+    /// Whether the item does anything with the given rules
+    pub fn is_available(self, rules: &RuleSet) -> bool {
+        match self {
+            MenuItem::Level => {
+                rules.gravity_interval_for(MAX_START_LEVEL) != rules.gravity_interval_for(1)
+            }
+            _ => true,
+        }
+    }
+
+    /// The next item that is available with the given rules
+    pub fn next(self, rules: &RuleSet) -> Self {
+        self.step(rules, 1)
+    }
+
+    /// The previous item that is available with the given rules
+    pub fn previous(self, rules: &RuleSet) -> Self {
+        self.step(rules, Self::ALL.len() - 1)
+    }
+
+    fn step(self, rules: &RuleSet, by: usize) -> Self {
+        let mut item = self;
+        loop {
+            item = Self::ALL[(item.index() + by) % Self::ALL.len()];
+            // Play is always available
+            if item.is_available(rules) {
+                return item;
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum StartMenuPhase {
-    Text,
-    ButtonStart,
-    ButtonPressed,
-    ButtonReleased,
+/// Region at the top of the display in which new pieces appear.
+///
+/// The rows above the playfield double as the preview of the next piece,
+/// so a piece that locks entirely inside them tops out the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnZone {
+    /// First row belonging to the playfield, all rows above are preview region
+    pub playfield_top: u8,
+    /// Row in which the lowest block of a newly spawned piece is placed
+    pub spawn_row: u8,
+    /// Column around which newly spawned pieces are centered
+    pub spawn_column: u8,
+}
+
+impl Default for SpawnZone {
+    fn default() -> Self {
+        Self {
+            playfield_top: 8,
+            spawn_row: 4,
+            spawn_column: 4,
+        }
+    }
+}
+
+impl SpawnZone {
+    /// Moves the piece so that it is centered on the spawn column and rests
+    /// on the spawn row, without sticking out at the top of the display
+    fn place(&self, piece: &mut Piece) {
+        let ((min_x, min_y), (max_x, max_y)) = piece.aabb();
+        let width = max_x - min_x + 1;
+        let dx = self.spawn_column as i16 - width / 2 - min_x;
+        let dy = (self.spawn_row as i16 - max_y).max(-min_y);
+        piece.move_by(dx, dy);
+    }
+
+    /// Whether all blocks of the piece are above the playfield
+    fn contains(&self, piece: &Piece) -> bool {
+        let ((_, _), (_, max_y)) = piece.aabb();
+        max_y < self.playfield_top as i16
+    }
 }
 
 pub struct InGameState {
     pub(crate) blocks: Blocks,
     score: u32,
+    pub(crate) lines: u32,
+    pub(crate) level: u32,
+    pieces: u32,
     pub(crate) current_piece: Piece,
     pub(crate) next_piece: Option<Piece>,
+    rules: RuleSet,
+    mode: GameMode,
+    /// Remaining pieces of a puzzle, `None` when pieces are drawn at random
+    piece_queue: Option<VecDeque<&'static PieceKind>>,
+    goal: Option<Goal>,
+    highscores: Highscores,
+    rng: SmallRng,
+    /// Set while full rows are being animated, input and gravity are suspended meanwhile
+    pub(crate) line_clear: Option<LineClear>,
+    /// Piece put aside with [`ButtonAction::Hold`]
+    pub(crate) held_piece: Option<&'static PieceKind>,
+    /// Whether the current piece was already swapped with the held one
+    hold_used: bool,
+    /// Gravity and all input except [`ButtonAction::Pause`] are suspended while paused
+    pub paused: bool,
     time_last_move: Instant,
 }
 
+/// Full rows that are wiped out from the center before they are removed
+pub(crate) struct LineClear {
+    pub(crate) rows: Vec<u8>,
+    started: Instant,
+    /// How many columns on each side of the center are already wiped
+    pub(crate) wiped: u8,
+}
+
 impl GameState {
     pub fn update(
         self,
         button_actions: Option<ButtonAction>,
         now: Instant,
-        add_score: impl FnMut(u32),
+        events: &mut Vec<GameEvent>,
     ) -> Self {
         match self {
-            GameState::StartMenu(state) => {
-                if button_actions.is_none() {
-                    GameState::StartMenu(state)
-                } else {
-                    GameState::InGame(InGameState::new())
+            GameState::StartMenu(state) => state.update(button_actions, now, events),
+            GameState::InGame(state) => state.update(button_actions, now, events),
+            GameState::GameOver(state) => state.update(button_actions, now),
+            GameState::HighscoreEntry(state) => state.update(button_actions, now, events),
+        }
+    }
+
+    /// The highscore table, including the score of a game that just ended
+    pub fn highscores(&self) -> &Highscores {
+        match self {
+            GameState::StartMenu(state) => &state.highscores,
+            GameState::InGame(state) => &state.highscores,
+            GameState::GameOver(state) => &state.highscores,
+            GameState::HighscoreEntry(state) => &state.highscores,
+        }
+    }
+
+    /// The score of the running or finished game
+    pub fn score(&self) -> Option<u32> {
+        match self {
+            GameState::StartMenu(_) => None,
+            GameState::InGame(state) => Some(state.score),
+            GameState::GameOver(state) => Some(state.score),
+            GameState::HighscoreEntry(state) => Some(state.score),
+        }
+    }
+}
+
+impl InStartState {
+    pub fn new(rules: RuleSet, now: Instant) -> Self {
+        Self::with_seed(rules, now, rand::random())
+    }
+
+    /// Creates the start menu with a fixed seed for drawing pieces,
+    /// so that games can be replayed exactly
+    pub fn with_seed(rules: RuleSet, now: Instant, seed: u64) -> Self {
+        Self {
+            item: MenuItem::Play,
+            showing_highscores: false,
+            ticks: 0,
+            last_update: now,
+            rules,
+            mode: GameMode::Marathon,
+            highscores: Highscores::default(),
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Replaces the highscore table, e.g. with one loaded from storage
+    pub fn with_highscores(mut self, highscores: Highscores) -> Self {
+        self.highscores = highscores;
+        self
+    }
+
+    fn update(
+        mut self,
+        button_action: Option<ButtonAction>,
+        now: Instant,
+        events: &mut Vec<GameEvent>,
+    ) -> GameState {
+        let Some(button_action) = button_action else {
+            // Advance the animation
+            if now.duration_since(self.last_update) >= MENU_TICK_INTERVAL {
+                self.ticks += 1;
+                self.last_update = now;
+            }
+            return GameState::StartMenu(self);
+        };
+
+        // Every press changes what is shown, so the animation starts over
+        self.ticks = 0;
+        self.last_update = now;
+
+        if self.showing_highscores {
+            self.showing_highscores = false;
+            return GameState::StartMenu(self);
+        }
+
+        match button_action {
+            ButtonAction::MoveLeft => self.item = self.item.previous(&self.rules),
+            ButtonAction::MoveRight => self.item = self.item.next(&self.rules),
+            ButtonAction::Rotate => match self.item {
+                MenuItem::Play => {
+                    events.push(GameEvent::GameStarted);
+                    events.push(GameEvent::PieceSpawned);
+                    let seed = self.rng.random();
+                    return GameState::InGame(InGameState {
+                        highscores: self.highscores,
+                        ..InGameState::new(self.rules, self.mode, now, seed)
+                    });
                 }
+                MenuItem::Mode => self.mode = self.mode.next(&self.rules.spawn_zone),
+                MenuItem::Level => {
+                    self.rules.start_level = self.rules.start_level % MAX_START_LEVEL + 1
+                }
+                MenuItem::Wrap => self.rules.wrap = !self.rules.wrap,
+                MenuItem::Highscores => self.showing_highscores = true,
+            },
+            ButtonAction::MoveDown | ButtonAction::Pause | ButtonAction::Hold => {}
+        }
+
+        GameState::StartMenu(self)
+    }
+}
+
+impl InGameOverState {
+    fn update(mut self, button_action: Option<ButtonAction>, now: Instant) -> GameState {
+        let elapsed = now.duration_since(self.started);
+        let height = DISPLAY_HEIGHT as u32;
+        let rows = (elapsed.as_millis() / CURTAIN_ROW_INTERVAL.as_millis()) as u32;
+        let curtain = CURTAIN_ROW_INTERVAL * 2 * height;
+
+        self.animation = if rows < height {
+            GameOverAnimation::Fill(rows as u8)
+        } else if rows < 2 * height {
+            GameOverAnimation::Clear((rows - height) as u8)
+        } else {
+            let shown = elapsed - curtain;
+            let blink = (shown.as_millis() / HIGHLIGHT_INTERVAL.as_millis()).is_multiple_of(2);
+            GameOverAnimation::Score {
+                highlight: self.new_highscore && blink,
             }
-            GameState::InGame(state) => state.update(button_actions, now, add_score),
-            GameState::GameOver(score) => {
-                if button_actions.is_none() {
-                    GameState::GameOver(score)
-                } else {
-                    GameState::StartMenu(InStartState {
-                        phase: StartMenuPhase::ButtonReleased,
-                        last_update: Instant::now() - Duration::from_millis(1000),
-                    })
+        };
+
+        // Presses during the animation must not skip the score
+        if button_action.is_none() || elapsed < curtain + MIN_SCORE_DURATION {
+            return GameState::GameOver(self);
+        }
+
+        if self.new_highscore {
+            return GameState::HighscoreEntry(InHighscoreEntryState {
+                score: self.score,
+                initials: ['A'; 3],
+                position: 0,
+                cursor_visible: true,
+                last_update: now,
+                highscores: self.highscores,
+                rules: self.rules,
+                mode: self.mode,
+                rng: self.rng,
+            });
+        }
+
+        GameState::StartMenu(InStartState {
+            mode: self.mode,
+            highscores: self.highscores,
+            rng: self.rng,
+            ..InStartState::new(self.rules, now)
+        })
+    }
+}
+
+impl InHighscoreEntryState {
+    fn update(
+        mut self,
+        button_action: Option<ButtonAction>,
+        now: Instant,
+        events: &mut Vec<GameEvent>,
+    ) -> GameState {
+        let initial = &mut self.initials[self.position];
+        match button_action {
+            None => {
+                if now.duration_since(self.last_update) >= CURSOR_INTERVAL {
+                    self.cursor_visible = !self.cursor_visible;
+                    self.last_update = now;
+                }
+                return GameState::HighscoreEntry(self);
+            }
+            Some(ButtonAction::MoveLeft) => *initial = Self::cycle_letter(*initial, 25),
+            Some(ButtonAction::MoveRight) => *initial = Self::cycle_letter(*initial, 1),
+            Some(ButtonAction::Rotate) => self.position = (self.position + 1) % self.initials.len(),
+            Some(ButtonAction::Pause | ButtonAction::Hold) => {}
+            Some(ButtonAction::MoveDown) => {
+                if let Some(rank) = self.highscores.add_score(self.initials, self.score) {
+                    events.push(GameEvent::NewHighscore { rank });
                 }
+
+                // Show where the score ended up
+                return GameState::StartMenu(InStartState {
+                    showing_highscores: true,
+                    mode: self.mode,
+                    highscores: self.highscores,
+                    rng: self.rng,
+                    ..InStartState::new(self.rules, now)
+                });
             }
         }
+
+        // Keep the cursor visible while editing
+        self.cursor_visible = true;
+        self.last_update = now;
+        GameState::HighscoreEntry(self)
+    }
+
+    /// Moves `by` letters forward in the alphabet, wrapping from Z to A
+    fn cycle_letter(letter: char, by: u8) -> char {
+        let index = (letter as u8).wrapping_sub(b'A') % 26;
+        (b'A' + (index + by) % 26) as char
     }
 }
 
 impl InGameState {
-    pub fn new() -> Self {
+    /// Starts a game, `seed` determines the sequence of random pieces.
+    /// A puzzle that does not exist starts a marathon instead.
+    pub fn new(rules: RuleSet, mode: GameMode, now: Instant, seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut blocks = Blocks {
+            data: [0; DISPLAY_HEIGHT as usize],
+            kinds: [[0; DISPLAY_WIDTH as usize]; DISPLAY_HEIGHT as usize],
+        };
+        let mut piece_queue = None;
+        let mut goal = None;
+
+        let puzzle = match mode {
+            GameMode::Puzzle(index) => bundled_puzzles().get(index),
+            GameMode::Marathon => None,
+        };
+        let mode = if puzzle.is_some() {
+            mode
+        } else {
+            GameMode::Marathon
+        };
+        if let Some(puzzle) = puzzle {
+            // The layout rests on the floor of the board
+            let top = blocks.data.len() - puzzle.rows.len();
+            blocks.data[top..].copy_from_slice(&puzzle.rows);
+
+            piece_queue = Some(puzzle.pieces.iter().copied().collect());
+            goal = Some(puzzle.goal);
+        }
+
+        let current_piece = Self::draw_piece(&mut piece_queue, &rules, &mut rng)
+            .expect("puzzles have at least one piece");
+
         Self {
-            blocks: Blocks {
-                data: [0; DISPLAY_HEIGHT as usize],
-            },
+            blocks,
             score: 0,
-            current_piece: Piece::random(),
+            lines: 0,
+            level: rules.start_level,
+            pieces: 0,
+            current_piece,
             next_piece: None,
-            time_last_move: Instant::now(),
+            rules,
+            mode,
+            piece_queue,
+            goal,
+            highscores: Highscores::default(),
+            rng,
+            line_clear: None,
+            held_piece: None,
+            hold_used: false,
+            paused: false,
+            time_last_move: now,
         }
     }
 
@@ -79,19 +536,50 @@ impl InGameState {
         mut self,
         button_action: Option<ButtonAction>,
         now: Instant,
-        mut add_score: impl FnMut(u32),
+        events: &mut Vec<GameEvent>,
     ) -> GameState {
+        if let Some(line_clear) = &mut self.line_clear {
+            let elapsed = now.duration_since(line_clear.started);
+            let duration = self.rules.line_clear_duration;
+            if elapsed < duration {
+                let half_width = DISPLAY_WIDTH as u128 / 2;
+                line_clear.wiped = (elapsed.as_micros() * (half_width + 1) / duration.as_micros())
+                    .min(half_width) as u8;
+                return GameState::InGame(self);
+            }
+
+            // Gravity restarts once the rows are gone
+            let rows = self.line_clear.take().unwrap().rows;
+            self.time_last_move = now;
+            if self.finish_lock(&rows, events) {
+                return self.game_over(now, events);
+            }
+            return GameState::InGame(self);
+        }
+
+        if matches!(button_action, Some(ButtonAction::Pause)) {
+            self.paused = !self.paused;
+            // Gravity starts over instead of catching up on the paused time
+            self.time_last_move = now;
+            return GameState::InGame(self);
+        }
+        if self.paused {
+            return GameState::InGame(self);
+        }
+
         let piece_events = button_action
-            .map(|button_action| match button_action {
-                ButtonAction::MoveLeft => PieceEvent::MoveBy(-1, 0),
-                ButtonAction::MoveRight => PieceEvent::MoveBy(1, 0),
-                ButtonAction::MoveDown => PieceEvent::Drop,
-                ButtonAction::Rotate => PieceEvent::Rotate(Rotation::Deg90),
+            .and_then(|button_action| match button_action {
+                ButtonAction::MoveLeft => Some(PieceEvent::MoveBy(-1, 0)),
+                ButtonAction::MoveRight => Some(PieceEvent::MoveBy(1, 0)),
+                ButtonAction::MoveDown => Some(PieceEvent::Drop),
+                ButtonAction::Rotate => Some(PieceEvent::Rotate(Rotation::Deg90)),
+                ButtonAction::Hold => Some(PieceEvent::Hold),
+                ButtonAction::Pause => None,
             })
             .into_iter()
             .chain({
-                let should_move =
-                    (now.duration_since(self.time_last_move)) >= Duration::from_millis(500);
+                let gravity_interval = self.rules.gravity_interval_for(self.level);
+                let should_move = (now.duration_since(self.time_last_move)) >= gravity_interval;
                 should_move.then(|| {
                     self.time_last_move = now;
                     PieceEvent::MoveBy(0, 1)
@@ -99,65 +587,251 @@ impl InGameState {
             });
 
         for piece_event in piece_events {
-            if self.update_piece_and_blocks(piece_event) {
-                add_score(self.score);
-                return GameState::GameOver(self.score);
+            if self.update_piece_and_blocks(piece_event, now, events) {
+                return self.game_over(now, events);
+            }
+
+            // Remaining events wait until the cleared rows are gone
+            if self.line_clear.is_some() {
+                break;
             }
         }
 
         GameState::InGame(self)
     }
 
+    fn game_over(self, now: Instant, events: &mut Vec<GameEvent>) -> GameState {
+        events.push(GameEvent::GameOver {
+            stats: self.stats(),
+        });
+
+        // Puzzle scores are not comparable with regular games.
+        // Qualifying scores are added once the initials are entered.
+        let new_highscore = match self.mode {
+            GameMode::Marathon => self.highscores.qualifies(self.score),
+            GameMode::Puzzle(_) => false,
+        };
+
+        GameState::GameOver(InGameOverState {
+            score: self.score,
+            new_highscore,
+            animation: GameOverAnimation::Fill(0),
+            blocks: self.blocks,
+            started: now,
+            highscores: self.highscores,
+            rules: self.rules,
+            mode: self.mode,
+            rng: self.rng,
+        })
+    }
+
+    pub fn stats(&self) -> GameStats {
+        GameStats {
+            score: self.score,
+            lines: self.lines,
+            level: self.level,
+            pieces: self.pieces,
+            mode: self.mode,
+        }
+    }
+
     /// Returns whether the game is over
-    fn update_piece_and_blocks(&mut self, piece_event: PieceEvent) -> bool {
+    fn update_piece_and_blocks(
+        &mut self,
+        piece_event: PieceEvent,
+        now: Instant,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
         let mut collision_piece = self.current_piece.clone();
         match piece_event {
-            PieceEvent::Drop => {
-                while !self.blocks.intersects(&collision_piece) {
-                    collision_piece.move_by(0, 1);
-                }
-                collision_piece.move_by(0, -1);
+            PieceEvent::Hold => {
+                self.hold_piece(events);
+                return false;
             }
+            PieceEvent::Drop => collision_piece = self.drop_position(),
             PieceEvent::MoveBy(dx, dy) => collision_piece.move_by(dx, dy),
             PieceEvent::Rotate(rotation) => collision_piece.rotate(rotation),
         }
 
         // Collissions with floor, walls and existing blocks
-        let will_intersect = self.blocks.intersects(&collision_piece);
+        let will_intersect = self.blocks.intersects(&collision_piece, self.rules.wrap);
         let moved_down = matches!(piece_event,  PieceEvent::MoveBy(_, y) if y > 0)
             | matches!(piece_event, PieceEvent::Drop);
         match (will_intersect, moved_down) {
             (true, true) => {
                 // Place piece on top of existing blocks
                 self.blocks.place_piece(&self.current_piece);
+                self.pieces += 1;
+                events.push(GameEvent::PieceLocked);
 
-                // Remove full rows of blocks
-                self.score += self.blocks.remove_full_rows() * 10;
-                log::info!("Current highscore {}", self.score);
-
-                // Check if game is over
-                let game_over = self.blocks.data[7] != 0x00;
-                if game_over {
+                // Lock out: the piece never made it into the playfield
+                if self.rules.spawn_zone.contains(&self.current_piece) {
+                    log::info!("Lock out");
                     return true;
                 }
 
-                self.current_piece = self.next_piece.take().unwrap_or(Piece::random());
+                let rows = self.blocks.full_rows();
+                if !rows.is_empty() {
+                    let count = rows.len() as u32;
+                    self.score += self.rules.points_for(count);
+                    self.lines += count;
+                    log::info!("Current highscore {}", self.score);
+                    events.push(GameEvent::LinesCleared {
+                        count,
+                        rows: rows.clone(),
+                    });
+
+                    let level = self.rules.start_level + self.lines / self.rules.lines_per_level;
+                    if level > self.level {
+                        self.level = level;
+                        events.push(GameEvent::LevelUp { level });
+                    }
+
+                    // Show the full rows for a moment before they collapse
+                    if !self.rules.line_clear_duration.is_zero() {
+                        self.line_clear = Some(LineClear {
+                            rows,
+                            started: now,
+                            wiped: 0,
+                        });
+                        return false;
+                    }
+                }
+
+                return self.finish_lock(&rows, events);
             }
             (true, _) => {}
             (false, _) => self.current_piece = collision_piece,
         }
 
+        // Only preview the next piece once the current one left the preview region
         let ((_, min_y), _) = self.current_piece.aabb();
-        if self.next_piece.is_none() && min_y > 8 {
-            self.next_piece = Some(Piece::random());
+        if self.next_piece.is_none() && min_y >= self.rules.spawn_zone.playfield_top as i16 {
+            self.next_piece = Self::draw_piece(&mut self.piece_queue, &self.rules, &mut self.rng);
         }
 
         false
     }
+
+    /// Where the current piece lands when it is dropped
+    pub(crate) fn drop_position(&self) -> Piece {
+        let mut piece = self.current_piece.clone();
+        while !self.blocks.intersects(&piece, self.rules.wrap) {
+            piece.move_by(0, 1);
+        }
+        piece.move_by(0, -1);
+        piece
+    }
+
+    /// Removes the cleared rows and spawns the next piece.
+    /// Returns whether the game is over
+    fn finish_lock(&mut self, rows: &[u8], events: &mut Vec<GameEvent>) -> bool {
+        self.blocks.remove_rows(rows);
+
+        if let Some(solved) = self.puzzle_result() {
+            log::info!("Puzzle finished, solved: {solved}");
+            events.push(GameEvent::PuzzleFinished { solved });
+            return true;
+        }
+
+        let next_piece = match self
+            .next_piece
+            .take()
+            .or_else(|| Self::draw_piece(&mut self.piece_queue, &self.rules, &mut self.rng))
+        {
+            Some(next_piece) => next_piece,
+            None => {
+                log::info!("Puzzle ran out of pieces");
+                events.push(GameEvent::PuzzleFinished { solved: false });
+                return true;
+            }
+        };
+        self.current_piece = next_piece;
+        self.hold_used = false;
+        events.push(GameEvent::PieceSpawned);
+
+        // Block out: the new piece overlaps the stack right away
+        if self.blocks.intersects(&self.current_piece, self.rules.wrap) {
+            log::info!("Block out");
+            return true;
+        }
+
+        false
+    }
+
+    /// Swaps the current piece with the held one, or with the next piece if
+    /// nothing is held yet. Only allowed once per piece.
+    fn hold_piece(&mut self, events: &mut Vec<GameEvent>) {
+        if self.hold_used {
+            return;
+        }
+
+        let replacement = match self.held_piece {
+            Some(kind) => {
+                let mut piece = Piece::new(0, 0, kind);
+                self.rules.spawn_zone.place(&mut piece);
+                Some(piece)
+            }
+            None => self
+                .next_piece
+                .take()
+                .or_else(|| Self::draw_piece(&mut self.piece_queue, &self.rules, &mut self.rng)),
+        };
+        // A puzzle may have run out of pieces
+        let Some(replacement) = replacement else {
+            return;
+        };
+
+        if self.blocks.intersects(&replacement, self.rules.wrap) {
+            // No room to swap, a piece taken from the queue stays next
+            if self.held_piece.is_none() {
+                self.next_piece = Some(replacement);
+            }
+            return;
+        }
+
+        self.held_piece = Some(self.current_piece.kind());
+        self.current_piece = replacement;
+        self.hold_used = true;
+        events.push(GameEvent::PieceSpawned);
+    }
+
+    /// Takes the next piece of the puzzle, or a random one outside of puzzles,
+    /// placed in the spawn zone. Returns `None` once a puzzle is out of pieces.
+    fn draw_piece(
+        piece_queue: &mut Option<VecDeque<&'static PieceKind>>,
+        rules: &RuleSet,
+        rng: &mut SmallRng,
+    ) -> Option<Piece> {
+        let mut piece = match piece_queue {
+            Some(piece_queue) => Piece::new(0, 0, piece_queue.pop_front()?),
+            None => Piece::random(rules.piece_set, rng),
+        };
+        rules.spawn_zone.place(&mut piece);
+        Some(piece)
+    }
+
+    /// Whether the goal of the puzzle was reached, or `None` while it is undecided
+    fn puzzle_result(&self) -> Option<bool> {
+        match self.goal? {
+            Goal::ClearAll => self.blocks.is_empty().then_some(true),
+            Goal::ClearLines { lines, pieces } => {
+                if self.lines >= lines {
+                    Some(true)
+                } else if self.pieces >= pieces {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+        }
+    }
 }
 
 pub struct Blocks {
     data: [u8; DISPLAY_HEIGHT as usize],
+    /// Name of the piece each block belongs to as ASCII, 0 for blocks of a puzzle layout
+    kinds: [[u8; DISPLAY_WIDTH as usize]; DISPLAY_HEIGHT as usize],
 }
 
 impl Blocks {
@@ -173,51 +847,72 @@ impl Blocks {
         self.data[y as usize] & mask != 0x00
     }
 
-    fn set(&mut self, x: i16, y: i16) {
+    /// Name of the piece a block belongs to, `None` for empty cells and
+    /// blocks that were not placed by a piece
+    pub(crate) fn kind_name(&self, x: i16, y: i16) -> Option<char> {
+        if x < 0 || x >= DISPLAY_WIDTH as i16 || y < 0 || y >= DISPLAY_HEIGHT as i16 {
+            return None;
+        }
+        if !self.get(x, y) {
+            return None;
+        }
+
+        match self.kinds[y as usize][x as usize] {
+            0 => None,
+            name => Some(name as char),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.iter().all(|&row| row == 0x00)
+    }
+
+    fn set(&mut self, x: i16, y: i16, name: char) {
         if x < 0 || x >= DISPLAY_WIDTH as i16 || y < 0 || y >= DISPLAY_HEIGHT as i16 {
             return;
         }
 
         let mask = 0b1000_0000 >> x;
         self.data[y as usize] |= mask;
+        self.kinds[y as usize][x as usize] = if name.is_ascii() { name as u8 } else { 0 };
     }
 
-    fn intersects(&self, piece: &Piece) -> bool {
-        piece
-            .block_positions()
-            .any(|(x, y)| self.get(wrap_x(x) as i16, y as i16))
+    /// Whether the piece overlaps blocks or the floor, and the walls if
+    /// pieces do not wrap around the sides of the board
+    fn intersects(&self, piece: &Piece, wrap: bool) -> bool {
+        piece.block_positions().any(|(x, y)| {
+            if wrap {
+                self.get(wrap_x(x) as i16, y)
+            } else {
+                x < 0 || x >= DISPLAY_WIDTH as i16 || self.get(x, y)
+            }
+        })
     }
 
     fn place_piece(&mut self, piece: &Piece) {
         for (x, y) in piece.block_positions() {
-            self.set(wrap_x(x) as i16, y as i16);
+            self.set(wrap_x(x) as i16, y, piece.kind().name);
         }
     }
 
-    /// Returns how many rows were removed
-    fn remove_full_rows(&mut self) -> u32 {
-        let mut removed = 0;
-        let height = self.data.len();
-
-        // Start from the bottom row and work upward
-        let mut y = height as isize - 1;
-        while y >= 0 {
-            if self.data[y as usize] == 0xff {
-                removed += 1;
-
-                // Shift all rows above down by one
-                for row in (1..=y as usize).rev() {
-                    self.data[row] = self.data[row - 1];
-                }
-                self.data[0] = 0x00;
+    /// Returns the indices of all rows without gaps, from top to bottom
+    fn full_rows(&self) -> Vec<u8> {
+        (0..DISPLAY_HEIGHT)
+            .filter(|&y| self.data[y as usize] == 0xff)
+            .collect()
+    }
 
-                // Stay on same y index to check the shifted row
-            } else {
-                y -= 1;
-            }
+    /// Removes the given rows, which have to be sorted from top to bottom,
+    /// and lets the rows above collapse
+    fn remove_rows(&mut self, rows: &[u8]) {
+        // Work from the top down, so shifting the rows above a removed row
+        // never moves one of the rows that are still to be removed
+        for &y in rows {
+            self.data.copy_within(0..y as usize, 1);
+            self.data[0] = 0x00;
+            self.kinds.copy_within(0..y as usize, 1);
+            self.kinds[0] = [0; DISPLAY_WIDTH as usize];
         }
-
-        removed
     }
 }
 
@@ -225,12 +920,72 @@ pub enum PieceEvent {
     Drop,
     MoveBy(i16, i16),
     Rotate(Rotation),
+    Hold,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ButtonAction {
     MoveLeft,
     MoveRight,
     MoveDown,
     Rotate,
+    /// Suspends or resumes a running game
+    Pause,
+    /// Puts the current piece aside, or swaps it with the piece put aside before
+    Hold,
+}
+
+impl ButtonAction {
+    pub const ALL: [ButtonAction; 6] = [
+        ButtonAction::MoveLeft,
+        ButtonAction::MoveRight,
+        ButtonAction::MoveDown,
+        ButtonAction::Rotate,
+        ButtonAction::Pause,
+        ButtonAction::Hold,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            ButtonAction::MoveLeft => "move_left",
+            ButtonAction::MoveRight => "move_right",
+            ButtonAction::MoveDown => "move_down",
+            ButtonAction::Rotate => "rotate",
+            ButtonAction::Pause => "pause",
+            ButtonAction::Hold => "hold",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_item_is_skipped_without_gravity_speedup() {
+        let classic = RuleSet::preset(Preset::Classic);
+        assert_eq!(MenuItem::Mode.next(&classic), MenuItem::Wrap);
+        assert_eq!(MenuItem::Wrap.previous(&classic), MenuItem::Mode);
+
+        let modern = RuleSet::preset(Preset::Modern);
+        assert_eq!(MenuItem::Mode.next(&modern), MenuItem::Level);
+        assert_eq!(MenuItem::Wrap.previous(&modern), MenuItem::Level);
+    }
+
+    #[test]
+    fn unknown_puzzle_starts_a_marathon() {
+        let rules = RuleSet::preset(Preset::Classic);
+        let mode = GameMode::Puzzle(usize::MAX);
+        let state = InGameState::new(rules.clone(), mode, Instant::now(), 0);
+        assert_eq!(state.mode, GameMode::Marathon);
+        assert!(state.piece_queue.is_none());
+
+        assert_eq!(mode.next(&rules.spawn_zone), GameMode::Marathon);
+        let last = bundled_puzzles().len() - 1;
+        assert_eq!(mode.previous(&rules.spawn_zone), GameMode::Puzzle(last));
+    }
 }
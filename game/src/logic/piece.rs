@@ -1,115 +1,176 @@
 use rand::Rng;
 
-#[derive(Clone, Copy)]
-enum PieceKind {
+/// Data-driven description of a piece shape.
+///
+/// The rotation pivot is given in half cells, so it can either sit on the
+/// center of a cell (both coordinates odd) or on the corner between four
+/// cells (both coordinates even). `(1, 1)` is the center of cell `(0, 0)`,
+/// `(2, 2)` the corner it shares with cell `(1, 1)`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PieceKind {
+    /// Letter the piece is known by, e.g. in puzzle definitions
+    pub name: char,
+    /// Block positions in spawn orientation
+    pub cells: &'static [(i8, i8)],
+    /// Rotation center in half cells
+    pub pivot: (i8, i8),
+}
+
+impl PieceKind {
+    const fn new(name: char, cells: &'static [(i8, i8)], pivot: (i8, i8)) -> Self {
+        Self { name, cells, pivot }
+    }
+}
+
+/// The seven classic pieces made of four blocks
+pub const TETROMINOES: &[PieceKind] = &[
     // ● ●
     // ● ●
-    O,
+    PieceKind::new('O', &[(0, 0), (1, 0), (0, 1), (1, 1)], (2, 2)),
     // ● ◌
     // ● ●
     // ● ◌
-    T,
+    PieceKind::new('T', &[(0, 0), (0, 1), (1, 1), (0, 2)], (1, 3)),
     // ● ◌
     // ● ●
     // ◌ ●
-    S,
+    PieceKind::new('S', &[(0, 0), (0, 1), (1, 1), (1, 2)], (1, 3)),
     // ◌ ●
     // ● ●
     // ● ◌
-    Z,
+    PieceKind::new('Z', &[(1, 0), (0, 1), (1, 1), (0, 2)], (3, 3)),
     // ◌ ●
     // ◌ ●
     // ● ●
-    J,
+    PieceKind::new('J', &[(1, 0), (1, 1), (0, 2), (1, 2)], (3, 3)),
     // ● ◌
     // ● ◌
     // ● ●
-    L,
+    PieceKind::new('L', &[(0, 0), (0, 1), (0, 2), (1, 2)], (1, 3)),
     // ● ● ● ●
-    I,
+    PieceKind::new('I', &[(0, 0), (1, 0), (2, 0), (3, 0)], (4, 2)),
+];
+
+/// The twelve pieces made of five blocks
+pub const PENTOMINOES: &[PieceKind] = &[
+    // ◌ ● ●
+    // ● ● ◌
+    // ◌ ● ◌
+    PieceKind::new('F', &[(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)], (3, 3)),
+    // ● ● ● ● ●
+    PieceKind::new('I', &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)], (5, 1)),
+    // ● ◌
+    // ● ◌
+    // ● ◌
+    // ● ●
+    PieceKind::new('L', &[(0, 0), (0, 1), (0, 2), (0, 3), (1, 3)], (1, 5)),
+    // ◌ ●
+    // ◌ ●
+    // ● ●
+    // ● ◌
+    PieceKind::new('N', &[(1, 0), (1, 1), (0, 2), (1, 2), (0, 3)], (3, 5)),
+    // ● ●
+    // ● ●
+    // ● ◌
+    PieceKind::new('P', &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)], (1, 3)),
+    // ● ● ●
+    // ◌ ● ◌
+    // ◌ ● ◌
+    PieceKind::new('T', &[(0, 0), (1, 0), (2, 0), (1, 1), (1, 2)], (3, 3)),
+    // ● ◌ ●
+    // ● ● ●
+    PieceKind::new('U', &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)], (3, 3)),
+    // ● ◌ ◌
+    // ● ◌ ◌
+    // ● ● ●
+    PieceKind::new('V', &[(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)], (3, 3)),
+    // ● ◌ ◌
+    // ● ● ◌
+    // ◌ ● ●
+    PieceKind::new('W', &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)], (3, 3)),
+    // ◌ ● ◌
+    // ● ● ●
+    // ◌ ● ◌
+    PieceKind::new('X', &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)], (3, 3)),
+    // ◌ ●
+    // ● ●
+    // ◌ ●
+    // ◌ ●
+    PieceKind::new('Y', &[(1, 0), (0, 1), (1, 1), (1, 2), (1, 3)], (3, 3)),
+    // ● ● ◌
+    // ◌ ● ◌
+    // ◌ ● ●
+    PieceKind::new('Z', &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)], (3, 3)),
+];
+
+/// Small pieces made of three blocks, e.g. for younger players
+pub const TROMINOES: &[PieceKind] = &[
+    // ● ● ●
+    PieceKind::new('I', &[(0, 0), (1, 0), (2, 0)], (3, 1)),
+    // ● ◌
+    // ● ●
+    PieceKind::new('L', &[(0, 0), (0, 1), (1, 1)], (1, 3)),
+];
+
+/// Selects the table of pieces a game is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceSet {
+    Tetrominoes,
+    Pentominoes,
+    Trominoes,
 }
 
-impl PieceKind {
-    const fn block_offsets(self) -> &'static [(i8, i8)] {
+impl PieceSet {
+    pub const ALL: [PieceSet; 3] = [
+        PieceSet::Tetrominoes,
+        PieceSet::Pentominoes,
+        PieceSet::Trominoes,
+    ];
+
+    pub const fn kinds(self) -> &'static [PieceKind] {
         match self {
-            PieceKind::O => &[(0, 0), (1, 0), (0, 1), (1, 1)],
-            PieceKind::T => &[(0, 0), (0, 1), (1, 1), (0, 2)],
-            PieceKind::S => &[(0, 0), (0, 1), (1, 1), (1, 2)],
-            PieceKind::Z => &[(1, 0), (0, 1), (1, 1), (0, 2)],
-            PieceKind::J => &[(1, 0), (1, 1), (0, 2), (1, 2)],
-            PieceKind::L => &[(0, 0), (0, 1), (0, 2), (1, 2)],
-            PieceKind::I => &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            PieceSet::Tetrominoes => TETROMINOES,
+            PieceSet::Pentominoes => PENTOMINOES,
+            PieceSet::Trominoes => TROMINOES,
         }
     }
 
-    const fn offset_for_rotation(self, rotation: Rotation) -> (i16, i16) {
+    pub const fn name(self) -> &'static str {
         match self {
-            PieceKind::O => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (0, -1),
-                Rotation::Deg180 => (-1, -1),
-                Rotation::Deg270 => (-1, 0),
-            },
-            PieceKind::T => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (-1, -1),
-                Rotation::Deg180 => (-2, -1),
-                Rotation::Deg270 => (-2, 1),
-            },
-            PieceKind::S => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (0, 0),
-                Rotation::Deg180 => (-1, -1),
-                Rotation::Deg270 => (-1, 1),
-            },
-            PieceKind::Z => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (-1, 0),
-                Rotation::Deg180 => (-1, -1),
-                Rotation::Deg270 => (-2, 1),
-            },
-            PieceKind::L | PieceKind::J => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (-1, 0),
-                Rotation::Deg180 => (-1, 0),
-                Rotation::Deg270 => (-1, 1),
-            },
-            PieceKind::I => match rotation {
-                Rotation::Deg0 => (0, 0),
-                Rotation::Deg90 => (2, -2),
-                Rotation::Deg180 => (-1, -1),
-                Rotation::Deg270 => (0, -1),
-            },
+            PieceSet::Tetrominoes => "tetrominoes",
+            PieceSet::Pentominoes => "pentominoes",
+            PieceSet::Trominoes => "trominoes",
         }
     }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|set| set.name() == name)
+    }
+
+    /// Looks up a piece of this set by its letter
+    pub fn find(self, name: char) -> Option<&'static PieceKind> {
+        self.kinds().iter().find(|kind| kind.name == name)
+    }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Piece {
     x: i16,
     y: i16,
-    kind: PieceKind,
+    kind: &'static PieceKind,
     rotation: Rotation,
 }
 
 impl Piece {
-    pub fn random() -> Self {
-        const BASE_VARIANTS: [Piece; 7] = [
-            Piece::new(3, 3, PieceKind::O),
-            Piece::new(3, 2, PieceKind::T),
-            Piece::new(3, 2, PieceKind::S),
-            Piece::new(3, 2, PieceKind::Z),
-            Piece::new(3, 2, PieceKind::J),
-            Piece::new(3, 2, PieceKind::L),
-            Piece::new(2, 3, PieceKind::I),
-        ];
-
-        let mut rng = rand::rng();
-        let variant_index = rng.random_range(0..BASE_VARIANTS.len());
-        BASE_VARIANTS[variant_index].clone()
-    }
-
-    const fn new(x: i16, y: i16, kind: PieceKind) -> Self {
+    /// Draws a piece from the given set, positioned at the origin
+    pub fn random(set: PieceSet, rng: &mut impl Rng) -> Self {
+        let kinds = set.kinds();
+
+        let variant_index = rng.random_range(0..kinds.len());
+        Piece::new(0, 0, &kinds[variant_index])
+    }
+
+    pub const fn new(x: i16, y: i16, kind: &'static PieceKind) -> Self {
         Self {
             x,
             y,
@@ -118,6 +179,10 @@ impl Piece {
         }
     }
 
+    pub fn kind(&self) -> &'static PieceKind {
+        self.kind
+    }
+
     pub fn rotate(&mut self, by: Rotation) {
         self.rotation = Rotation::from_u16((self.rotation.to_u16() + by.to_u16()) % 360);
     }
@@ -132,40 +197,42 @@ impl Piece {
         self.y += dy;
     }
 
+    /// Returns the top left and bottom right corner of the bounding box
     pub fn aabb(&self) -> ((i16, i16), (i16, i16)) {
-        (
-            self.block_positions().min().unwrap(),
-            self.block_positions().max().unwrap(),
+        self.block_positions().fold(
+            ((i16::MAX, i16::MAX), (i16::MIN, i16::MIN)),
+            |((min_x, min_y), (max_x, max_y)), (x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
         )
     }
 
     pub fn block_positions(&self) -> impl Iterator<Item = (i16, i16)> {
-        let (width, height) = match self.kind {
-            PieceKind::I => (4, 1),
-            PieceKind::O => (2, 2),
-            PieceKind::T => (2, 3),
-            PieceKind::S => (2, 3),
-            PieceKind::Z => (2, 3),
-            PieceKind::J => (2, 3),
-            PieceKind::L => (2, 3),
-        };
-
-        let (mx, my) = self.kind.offset_for_rotation(self.rotation);
-
-        self.kind.block_offsets().iter().map(move |&(ox, oy)| {
+        let (px, py) = self.kind.pivot;
+        let (px, py) = (px as i16, py as i16);
+
+        self.kind.cells.iter().map(move |&(cx, cy)| {
+            // Work in half cells, relative to the pivot
+            let dx = 2 * cx as i16 + 1 - px;
+            let dy = 2 * cy as i16 + 1 - py;
+
+            // Rotate clockwise, keeping in mind that y points down
             let (rx, ry) = match self.rotation {
-                Rotation::Deg0 => (ox, oy),
-                Rotation::Deg90 => (oy, width - ox),
-                Rotation::Deg180 => (width - ox, height - oy),
-                Rotation::Deg270 => (height - oy, ox),
+                Rotation::Deg0 => (dx, dy),
+                Rotation::Deg90 => (-dy, dx),
+                Rotation::Deg180 => (-dx, -dy),
+                Rotation::Deg270 => (dy, -dx),
             };
 
-            (self.x + rx as i16 + mx, self.y + ry as i16 + my)
+            (
+                self.x + (px + rx - 1).div_euclid(2),
+                self.y + (py + ry - 1).div_euclid(2),
+            )
         })
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub enum Rotation {
     Deg0,
     Deg90,
@@ -193,3 +260,53 @@ impl Rotation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Blocks of the tetromino with the given name after each further quarter turn
+    fn rotations(name: char) -> Vec<Vec<(i16, i16)>> {
+        let mut piece = Piece::new(0, 0, PieceSet::Tetrominoes.find(name).unwrap());
+        (0..4)
+            .map(|_| {
+                let mut blocks: Vec<_> = piece.block_positions().collect();
+                blocks.sort_unstable();
+                piece.rotate(Rotation::Deg90);
+                blocks
+            })
+            .collect()
+    }
+
+    #[test]
+    fn o_stays_in_place() {
+        let blocks = vec![(0, 0), (0, 1), (1, 0), (1, 1)];
+        assert_eq!(rotations('O'), vec![blocks; 4]);
+    }
+
+    #[test]
+    fn i_turns_around_the_center_of_its_box() {
+        assert_eq!(
+            rotations('I'),
+            [
+                vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+                vec![(2, -1), (2, 0), (2, 1), (2, 2)],
+                vec![(0, 1), (1, 1), (2, 1), (3, 1)],
+                vec![(1, -1), (1, 0), (1, 1), (1, 2)],
+            ]
+        );
+    }
+
+    #[test]
+    fn t_turns_around_its_middle_block() {
+        assert_eq!(
+            rotations('T'),
+            [
+                vec![(0, 0), (0, 1), (0, 2), (1, 1)],
+                vec![(-1, 1), (0, 1), (0, 2), (1, 1)],
+                vec![(-1, 1), (0, 0), (0, 1), (0, 2)],
+                vec![(-1, 1), (0, 0), (0, 1), (1, 1)],
+            ]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use super::SpawnZone;
+use super::piece::{PieceKind, PieceSet};
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+/// Puzzles shipped with the game, see [`bundled_puzzles`]
+const PUZZLE_PACK: &str = include_str!("puzzles.txt");
+
+/// Rows available for the starting layout below the preview region of the spawn zone
+pub fn max_rows(spawn_zone: &SpawnZone) -> usize {
+    (DISPLAY_HEIGHT as usize).saturating_sub(spawn_zone.playfield_top as usize)
+}
+
+/// A predefined board with a fixed sequence of pieces and a goal.
+///
+/// Puzzles are written as text, one `key: value` line per setting followed by
+/// the rows of the board from top to bottom, using `#` for a block and `.` for
+/// an empty cell:
+///
+/// ```text
+/// name: Gap
+/// set: tetrominoes
+/// pieces: I O
+/// goal: lines 1 in 2
+/// ##....##
+/// ```
+///
+/// `set` is optional and defaults to the tetrominoes. The goal is either
+/// `clear all` or `lines <N> in <M>`. The rows rest on the floor of the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub name: String,
+    pub piece_set: PieceSet,
+    pub pieces: Vec<&'static PieceKind>,
+    pub goal: Goal,
+    /// Starting layout from top to bottom, one bit per column
+    pub rows: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Remove every block from the board
+    ClearAll,
+    /// Clear at least `lines` lines using no more than `pieces` pieces
+    ClearLines { lines: u32, pieces: u32 },
+}
+
+impl Puzzle {
+    pub fn parse(string: &str) -> Result<Self, ParsePuzzleError> {
+        let mut name = None;
+        let mut piece_set = PieceSet::Tetrominoes;
+        let mut piece_names = None;
+        let mut goal = None;
+        let mut rows = Vec::new();
+
+        for line in string
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "name" => name = Some(value.to_string()),
+                    "set" => {
+                        piece_set = PieceSet::from_name(value)
+                            .ok_or(ParsePuzzleError::InvalidValue("set"))?
+                    }
+                    "pieces" => piece_names = Some(value),
+                    "goal" => goal = Some(parse_goal(value)?),
+                    key => return Err(ParsePuzzleError::UnknownKey(key.to_string())),
+                }
+            } else {
+                rows.push(parse_row(line)?);
+            }
+        }
+
+        let pieces = piece_names
+            .ok_or(ParsePuzzleError::MissingKey("pieces"))?
+            .split_whitespace()
+            .map(|piece_name| {
+                let mut chars = piece_name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(letter), None) => piece_set
+                        .find(letter)
+                        .ok_or(ParsePuzzleError::UnknownPiece(letter)),
+                    _ => Err(ParsePuzzleError::InvalidValue("pieces")),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if pieces.is_empty() {
+            return Err(ParsePuzzleError::InvalidValue("pieces"));
+        }
+
+        if rows.len() > DISPLAY_HEIGHT as usize {
+            return Err(ParsePuzzleError::TooManyRows);
+        }
+
+        Ok(Self {
+            name: name.ok_or(ParsePuzzleError::MissingKey("name"))?,
+            piece_set,
+            pieces,
+            goal: goal.ok_or(ParsePuzzleError::MissingKey("goal"))?,
+            rows,
+        })
+    }
+
+    /// Whether the starting layout fits below the preview region of the spawn zone
+    pub fn fits(&self, spawn_zone: &SpawnZone) -> bool {
+        self.rows.len() <= max_rows(spawn_zone)
+    }
+
+    /// Parses several puzzles separated by lines containing only `---`
+    pub fn parse_pack(string: &str) -> Result<Vec<Self>, ParsePuzzleError> {
+        let mut puzzles = Vec::new();
+        let mut current = String::new();
+
+        for line in string.lines().chain(["---"]) {
+            if line.trim() == "---" {
+                if !current.trim().is_empty() {
+                    puzzles.push(Self::parse(&current)?);
+                }
+                current.clear();
+            } else {
+                current.push_str(line);
+                current.push('\n');
+            }
+        }
+
+        Ok(puzzles)
+    }
+}
+
+/// Returns the puzzles shipped with the game
+pub fn bundled_puzzles() -> &'static [Puzzle] {
+    static PUZZLES: OnceLock<Vec<Puzzle>> = OnceLock::new();
+    PUZZLES.get_or_init(|| Puzzle::parse_pack(PUZZLE_PACK).expect("bundled puzzles are valid"))
+}
+
+fn parse_goal(value: &str) -> Result<Goal, ParsePuzzleError> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    match words.as_slice() {
+        ["clear", "all"] => Ok(Goal::ClearAll),
+        ["lines", lines, "in", pieces] => Ok(Goal::ClearLines {
+            lines: lines
+                .parse()
+                .map_err(|_| ParsePuzzleError::InvalidValue("goal"))?,
+            pieces: pieces
+                .parse()
+                .map_err(|_| ParsePuzzleError::InvalidValue("goal"))?,
+        }),
+        _ => Err(ParsePuzzleError::InvalidValue("goal")),
+    }
+}
+
+fn parse_row(line: &str) -> Result<u8, ParsePuzzleError> {
+    if line.chars().count() != DISPLAY_WIDTH as usize {
+        return Err(ParsePuzzleError::InvalidRow(line.to_string()));
+    }
+
+    line.chars().try_fold(0u8, |row, char| match char {
+        '#' => Ok(row << 1 | 1),
+        '.' => Ok(row << 1),
+        _ => Err(ParsePuzzleError::InvalidRow(line.to_string())),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePuzzleError {
+    /// A required setting is not present
+    MissingKey(&'static str),
+    /// A setting that puzzles do not have
+    UnknownKey(String),
+    /// The value of the given setting could not be parsed
+    InvalidValue(&'static str),
+    /// A piece letter that is not part of the selected piece set
+    UnknownPiece(char),
+    /// A board row with the wrong width or characters other than `#` and `.`
+    InvalidRow(String),
+    /// The layout is higher than the board, see [`Puzzle::fits`] for
+    /// whether it fits below the preview region
+    TooManyRows,
+}
+
+impl fmt::Display for ParsePuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePuzzleError::MissingKey(key) => write!(f, "missing '{key}'"),
+            ParsePuzzleError::UnknownKey(key) => write!(f, "unknown key '{key}'"),
+            ParsePuzzleError::InvalidValue(key) => write!(f, "invalid value for '{key}'"),
+            ParsePuzzleError::UnknownPiece(name) => write!(f, "unknown piece '{name}'"),
+            ParsePuzzleError::InvalidRow(row) => write!(f, "invalid row '{row}'"),
+            ParsePuzzleError::TooManyRows => write!(f, "at most {DISPLAY_HEIGHT} rows are allowed"),
+        }
+    }
+}
+
+impl std::error::Error for ParsePuzzleError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::logic::{ButtonAction, GameEvent, RuleSet};
+    use crate::simulator::{Simulator, placement_script};
+
+    /// How often each piece is rotated and how far it is moved before it is dropped
+    const SOLUTIONS: &[(&str, &[(usize, i16)])] = &[
+        ("First line", &[(0, 0)]),
+        ("Square", &[(0, 0)]),
+        ("Steps", &[(0, -4), (0, -1), (0, -4)]),
+        ("Well", &[(1, 3)]),
+        ("Cross", &[(0, -2)]),
+    ];
+
+    /// Starts the puzzle from the start menu and plays the moves, returns
+    /// whether the puzzle was solved once it is finished
+    fn play(index: usize, moves: &[(usize, i16)]) -> Option<bool> {
+        use ButtonAction::*;
+
+        let mut script = vec![Some(MoveRight)];
+        script.extend(std::iter::repeat_n(Some(Rotate), index + 1));
+        script.extend([Some(MoveLeft), Some(Rotate)]);
+        // Let each piece lock and the rows clear
+        script.extend(placement_script(moves.iter().copied(), 20));
+
+        let mut simulator = Simulator::new(RuleSet::default(), 1, Duration::from_millis(50));
+        simulator
+            .run(script)
+            .into_iter()
+            .flat_map(|frame| frame.events)
+            .find_map(|event| match event {
+                GameEvent::PuzzleFinished { solved } => Some(solved),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn parses_bundled_puzzles() {
+        let puzzles = Puzzle::parse_pack(PUZZLE_PACK).unwrap();
+        assert_eq!(puzzles.len(), SOLUTIONS.len());
+        assert!(
+            puzzles
+                .iter()
+                .all(|puzzle| puzzle.fits(&SpawnZone::default()))
+        );
+    }
+
+    #[test]
+    fn solves_bundled_puzzles() {
+        for (index, puzzle) in bundled_puzzles().iter().enumerate() {
+            let (_, moves) = SOLUTIONS
+                .iter()
+                .find(|(name, _)| *name == puzzle.name)
+                .expect("every puzzle has a solution");
+            assert_eq!(play(index, moves), Some(true), "{}", puzzle.name);
+        }
+    }
+
+    #[test]
+    fn rejects_layouts_higher_than_the_board() {
+        let rows = "#.......\n".repeat(DISPLAY_HEIGHT as usize + 1);
+        let string = format!("name: Tall\npieces: I\ngoal: clear all\n{rows}");
+        assert_eq!(Puzzle::parse(&string), Err(ParsePuzzleError::TooManyRows));
+    }
+}
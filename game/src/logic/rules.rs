@@ -0,0 +1,338 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::SpawnZone;
+use super::piece::PieceSet;
+use crate::input::{Debounce, DebounceMode};
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+/// All tunable gameplay parameters in one place.
+///
+/// A rule set can be written to and read from a simple `key=value` text format,
+/// see [`RuleSet::serialize`] and [`RuleSet::deserialize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    /// Time between two gravity steps on the first level
+    pub gravity_interval: Duration,
+    /// How much shorter the gravity interval gets with every level
+    pub gravity_speedup: Duration,
+    /// Lower bound for the gravity interval on high levels
+    pub min_gravity_interval: Duration,
+    /// Number of cleared lines needed to advance one level
+    pub lines_per_level: u32,
+    /// Level a new game starts on, starting at 1
+    pub start_level: u32,
+    /// How bouncing button contacts are filtered
+    pub debounce: Debounce,
+    /// Where new pieces appear
+    pub spawn_zone: SpawnZone,
+    /// Which pieces the game is played with
+    pub piece_set: PieceSet,
+    /// Points for clearing one, two, three, ... lines at once.
+    /// Clearing more lines than listed awards the last entry.
+    pub line_clear_points: Vec<u32>,
+    /// How long full rows are animated before they are removed, zero removes them instantly
+    pub line_clear_duration: Duration,
+    /// Whether pieces leaving the board on one side come back on the other side
+    pub wrap: bool,
+}
+
+/// Named rule sets shipped with the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// The rules the game was originally built with
+    Classic,
+    /// Faster, with bonus points for multi-line clears and solid walls
+    Modern,
+    /// Slow and forgiving
+    Kids,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Classic, Preset::Modern, Preset::Kids];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Preset::Classic => "classic",
+            Preset::Modern => "modern",
+            Preset::Kids => "kids",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::preset(Preset::Classic)
+    }
+}
+
+impl RuleSet {
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Classic => Self {
+                gravity_interval: Duration::from_millis(500),
+                gravity_speedup: Duration::ZERO,
+                min_gravity_interval: Duration::from_millis(500),
+                lines_per_level: 10,
+                start_level: 1,
+                debounce: Debounce {
+                    mode: DebounceMode::Time,
+                    window: Duration::from_millis(30),
+                },
+                spawn_zone: SpawnZone::default(),
+                piece_set: PieceSet::Tetrominoes,
+                line_clear_points: vec![10, 20, 30, 40],
+                line_clear_duration: Duration::from_millis(300),
+                wrap: true,
+            },
+            Preset::Modern => Self {
+                gravity_interval: Duration::from_millis(400),
+                gravity_speedup: Duration::from_millis(30),
+                min_gravity_interval: Duration::from_millis(80),
+                lines_per_level: 10,
+                start_level: 1,
+                debounce: Debounce {
+                    mode: DebounceMode::Time,
+                    window: Duration::from_millis(15),
+                },
+                spawn_zone: SpawnZone::default(),
+                piece_set: PieceSet::Tetrominoes,
+                line_clear_points: vec![100, 300, 500, 800],
+                line_clear_duration: Duration::from_millis(200),
+                wrap: false,
+            },
+            Preset::Kids => Self {
+                gravity_interval: Duration::from_millis(900),
+                gravity_speedup: Duration::from_millis(20),
+                min_gravity_interval: Duration::from_millis(500),
+                lines_per_level: 5,
+                start_level: 1,
+                debounce: Debounce {
+                    mode: DebounceMode::Integrator,
+                    window: Duration::from_millis(40),
+                },
+                spawn_zone: SpawnZone::default(),
+                piece_set: PieceSet::Trominoes,
+                line_clear_points: vec![10, 20, 30],
+                line_clear_duration: Duration::from_millis(400),
+                wrap: true,
+            },
+        }
+    }
+
+    /// Time between two gravity steps on the given level (starting at 1)
+    pub fn gravity_interval_for(&self, level: u32) -> Duration {
+        // Large speedups from a stored rule set must not overflow
+        let speedup = self
+            .gravity_speedup
+            .checked_mul(level.saturating_sub(1))
+            .unwrap_or(Duration::MAX);
+        self.gravity_interval
+            .saturating_sub(speedup)
+            .max(self.min_gravity_interval)
+    }
+
+    /// Points awarded for clearing `lines` lines at once
+    pub fn points_for(&self, lines: u32) -> u32 {
+        if lines == 0 {
+            return 0;
+        }
+        let index = (lines as usize - 1).min(self.line_clear_points.len().saturating_sub(1));
+        self.line_clear_points.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn serialize(&self) -> String {
+        let points = self
+            .line_clear_points
+            .iter()
+            .map(u32::to_string)
+            .reduce(|accum, elem| accum + "," + &elem)
+            .unwrap_or_default();
+
+        format!(
+            "gravity_interval_ms={}\n\
+             gravity_speedup_ms={}\n\
+             min_gravity_interval_ms={}\n\
+             lines_per_level={}\n\
+             start_level={}\n\
+             debounce_mode={}\n\
+             debounce_ms={}\n\
+             playfield_top={}\n\
+             spawn_row={}\n\
+             spawn_column={}\n\
+             piece_set={}\n\
+             line_clear_points={}\n\
+             line_clear_ms={}\n\
+             wrap={}\n",
+            self.gravity_interval.as_millis(),
+            self.gravity_speedup.as_millis(),
+            self.min_gravity_interval.as_millis(),
+            self.lines_per_level,
+            self.start_level,
+            self.debounce.mode.name(),
+            self.debounce.window.as_millis(),
+            self.spawn_zone.playfield_top,
+            self.spawn_zone.spawn_row,
+            self.spawn_zone.spawn_column,
+            self.piece_set.name(),
+            points,
+            self.line_clear_duration.as_millis(),
+            self.wrap,
+        )
+    }
+
+    /// Parses `key=value` lines as written by [`RuleSet::serialize`].
+    ///
+    /// Keys that are missing keep the value of the preset named by an optional
+    /// `preset=<name>` line, or of [`Preset::Classic`] otherwise.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn deserialize(string: &str) -> Result<Self, ParseRuleSetError> {
+        let entries = string
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_once('=')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .ok_or_else(|| ParseRuleSetError::MissingSeparator(line.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let preset = match entries.iter().find(|(key, _)| *key == "preset") {
+            Some((_, name)) => Preset::from_name(name)
+                .ok_or_else(|| ParseRuleSetError::InvalidValue("preset".to_string()))?,
+            None => Preset::Classic,
+        };
+        let mut rules = Self::preset(preset);
+
+        for (key, value) in entries {
+            let invalid = || ParseRuleSetError::InvalidValue(key.to_string());
+            let millis = || {
+                value
+                    .parse()
+                    .map(Duration::from_millis)
+                    .map_err(|_| invalid())
+            };
+
+            match key {
+                "preset" => {}
+                "gravity_interval_ms" => rules.gravity_interval = millis()?,
+                "gravity_speedup_ms" => rules.gravity_speedup = millis()?,
+                "min_gravity_interval_ms" => rules.min_gravity_interval = millis()?,
+                "lines_per_level" => {
+                    rules.lines_per_level = value.parse().map_err(|_| invalid())?
+                }
+                "start_level" => rules.start_level = value.parse().map_err(|_| invalid())?,
+                "debounce_mode" => {
+                    rules.debounce.mode = DebounceMode::from_name(value).ok_or_else(invalid)?
+                }
+                "debounce_ms" => rules.debounce.window = millis()?,
+                "playfield_top" => {
+                    rules.spawn_zone.playfield_top = value.parse().map_err(|_| invalid())?
+                }
+                "spawn_row" => rules.spawn_zone.spawn_row = value.parse().map_err(|_| invalid())?,
+                "spawn_column" => {
+                    rules.spawn_zone.spawn_column = value.parse().map_err(|_| invalid())?
+                }
+                "piece_set" => rules.piece_set = PieceSet::from_name(value).ok_or_else(invalid)?,
+                "line_clear_points" => {
+                    rules.line_clear_points = value
+                        .split(',')
+                        .filter(|points| !points.trim().is_empty())
+                        .map(|points| points.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?
+                }
+                "line_clear_ms" => rules.line_clear_duration = millis()?,
+                "wrap" => rules.wrap = value.parse().map_err(|_| invalid())?,
+                _ => return Err(ParseRuleSetError::UnknownKey(key.to_string())),
+            }
+        }
+
+        if rules.lines_per_level == 0 {
+            return Err(ParseRuleSetError::InvalidValue(
+                "lines_per_level".to_string(),
+            ));
+        }
+        if rules.start_level == 0 {
+            return Err(ParseRuleSetError::InvalidValue("start_level".to_string()));
+        }
+        // The spawn zone has to be on the board
+        let zone = &rules.spawn_zone;
+        for (key, value, limit) in [
+            ("playfield_top", zone.playfield_top, DISPLAY_HEIGHT),
+            ("spawn_row", zone.spawn_row, DISPLAY_HEIGHT),
+            ("spawn_column", zone.spawn_column, DISPLAY_WIDTH),
+        ] {
+            if value >= limit {
+                return Err(ParseRuleSetError::InvalidValue(key.to_string()));
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRuleSetError {
+    /// A line without `=`
+    MissingSeparator(String),
+    /// A key that is not part of the rule set
+    UnknownKey(String),
+    /// The value for the given key could not be parsed
+    InvalidValue(String),
+}
+
+impl fmt::Display for ParseRuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRuleSetError::MissingSeparator(line) => write!(f, "missing '=' in line '{line}'"),
+            ParseRuleSetError::UnknownKey(key) => write!(f, "unknown key '{key}'"),
+            ParseRuleSetError::InvalidValue(key) => write!(f, "invalid value for '{key}'"),
+        }
+    }
+}
+
+impl std::error::Error for ParseRuleSetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_gravity_speedup_bottoms_out() {
+        let rules = RuleSet::deserialize(&format!("gravity_speedup_ms={}", u64::MAX)).unwrap();
+        assert_eq!(rules.gravity_interval_for(1), rules.gravity_interval);
+        assert_eq!(rules.gravity_interval_for(2), rules.min_gravity_interval);
+        assert_eq!(
+            rules.gravity_interval_for(u32::MAX),
+            rules.min_gravity_interval
+        );
+    }
+
+    #[test]
+    fn rejects_spawn_zone_outside_of_the_board() {
+        for line in ["playfield_top=32", "spawn_row=40", "spawn_column=8"] {
+            let key = line.split_once('=').unwrap().0.to_string();
+            assert_eq!(
+                RuleSet::deserialize(line),
+                Err(ParseRuleSetError::InvalidValue(key))
+            );
+        }
+        let rules = RuleSet::deserialize("playfield_top=31\nspawn_column=7").unwrap();
+        assert_eq!(rules.spawn_zone.playfield_top, 31);
+    }
+
+    #[test]
+    fn round_trips_every_preset() {
+        for preset in Preset::ALL {
+            let rules = RuleSet::preset(preset);
+            let string = format!("preset={}\n{}", preset.name(), rules.serialize());
+            assert_eq!(RuleSet::deserialize(&string), Ok(rules));
+        }
+    }
+}
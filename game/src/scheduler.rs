@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+/// Paces a main loop to a fixed tick rate.
+///
+/// The loop sleeps for [`TickScheduler::time_until_tick`], then wraps its work in
+/// [`TickScheduler::begin_tick`] and [`TickScheduler::end_tick`]. Ticks that were
+/// missed because a tick took too long are skipped rather than caught up, the game
+/// logic works on timestamps and does not need to see every tick.
+#[derive(Debug, Clone)]
+pub struct TickScheduler {
+    interval: Duration,
+    next_tick: Instant,
+    tick_started: Option<Instant>,
+    stats: FrameStats,
+}
+
+/// Timing of the ticks since the statistics were last taken
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Number of finished ticks
+    pub ticks: u32,
+    /// Number of ticks that changed the display
+    pub frames: u32,
+    /// Number of ticks that were skipped because the loop fell behind
+    pub missed: u32,
+    /// Shortest time spent working in a tick
+    pub min_busy: Duration,
+    /// Longest time spent working in a tick
+    pub max_busy: Duration,
+    /// Time spent working in all ticks together
+    pub total_busy: Duration,
+}
+
+impl TickScheduler {
+    /// The first tick is due right away
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            next_tick: now,
+            tick_started: None,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Creates a scheduler running the given number of ticks per second
+    pub fn with_rate(ticks_per_second: u32, now: Instant) -> Self {
+        Self::new(Duration::from_secs(1) / ticks_per_second.max(1), now)
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Time left until the next tick is due, zero when it is due already
+    pub fn time_until_tick(&self, now: Instant) -> Duration {
+        self.next_tick.saturating_duration_since(now)
+    }
+
+    /// Starts a tick and schedules the next one
+    pub fn begin_tick(&mut self, now: Instant) {
+        self.tick_started = Some(now);
+
+        let late = now.saturating_duration_since(self.next_tick);
+        let missed = (late.as_nanos() / self.interval.as_nanos().max(1)) as u32;
+        self.stats.missed += missed;
+        self.next_tick += self.interval * (missed + 1);
+    }
+
+    /// Finishes the tick started last, `rendered` tells whether the display was updated
+    pub fn end_tick(&mut self, now: Instant, rendered: bool) {
+        let Some(started) = self.tick_started.take() else {
+            return;
+        };
+        let busy = now.saturating_duration_since(started);
+
+        let stats = &mut self.stats;
+        if stats.ticks == 0 || busy < stats.min_busy {
+            stats.min_busy = busy;
+        }
+        stats.max_busy = stats.max_busy.max(busy);
+        stats.total_busy += busy;
+        stats.ticks += 1;
+        if rendered {
+            stats.frames += 1;
+        }
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// Returns the statistics collected so far and starts over
+    pub fn take_stats(&mut self) -> FrameStats {
+        std::mem::take(&mut self.stats)
+    }
+}
+
+impl FrameStats {
+    /// Average time spent working in a tick
+    pub fn average_busy(&self) -> Duration {
+        self.total_busy
+            .checked_div(self.ticks)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Share of the tick interval spent working, 1.0 means the loop never sleeps
+    pub fn load(&self, interval: Duration) -> f32 {
+        if interval.is_zero() {
+            return 0.0;
+        }
+        self.average_busy().as_secs_f32() / interval.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn late_tick_skips_the_missed_ticks() {
+        let start = Instant::now();
+        let mut scheduler = TickScheduler::new(INTERVAL, start);
+        assert_eq!(scheduler.time_until_tick(start), Duration::ZERO);
+
+        scheduler.begin_tick(start);
+        scheduler.end_tick(start + ms(2), false);
+        assert_eq!(scheduler.time_until_tick(start + ms(2)), ms(8));
+
+        // Due at 10 but started at 35, the ticks at 20 and 30 are skipped
+        scheduler.begin_tick(start + ms(35));
+        scheduler.end_tick(start + ms(36), false);
+        assert_eq!(scheduler.stats().missed, 2);
+        assert_eq!(scheduler.time_until_tick(start + ms(36)), ms(4));
+
+        // A slightly late tick keeps the schedule
+        scheduler.begin_tick(start + ms(41));
+        assert_eq!(scheduler.stats().missed, 2);
+        assert_eq!(scheduler.time_until_tick(start + ms(41)), ms(9));
+    }
+
+    #[test]
+    fn counts_frames_only_for_ticks_that_rendered() {
+        let start = Instant::now();
+        let mut scheduler = TickScheduler::new(INTERVAL, start);
+        for (tick, rendered) in [true, false, false, true, false].into_iter().enumerate() {
+            let now = start + INTERVAL * tick as u32;
+            scheduler.begin_tick(now);
+            scheduler.end_tick(now, rendered);
+        }
+        assert_eq!(scheduler.stats().ticks, 5);
+        assert_eq!(scheduler.stats().frames, 2);
+
+        // Ending a tick that never began changes nothing
+        scheduler.end_tick(start + ms(100), true);
+        assert_eq!(scheduler.stats().ticks, 5);
+    }
+
+    #[test]
+    fn collects_busy_times_until_taken() {
+        let start = Instant::now();
+        let mut scheduler = TickScheduler::new(INTERVAL, start);
+        for (tick, busy) in [3, 1, 5].into_iter().enumerate() {
+            let now = start + INTERVAL * tick as u32;
+            scheduler.begin_tick(now);
+            scheduler.end_tick(now + ms(busy), true);
+        }
+
+        let stats = scheduler.take_stats();
+        assert_eq!((stats.min_busy, stats.max_busy), (ms(1), ms(5)));
+        assert_eq!(stats.total_busy, ms(9));
+        assert_eq!(stats.average_busy(), ms(3));
+        assert!((stats.load(INTERVAL) - 0.3).abs() < 1e-6);
+
+        assert_eq!(scheduler.stats(), &FrameStats::default());
+        assert_eq!(FrameStats::default().average_busy(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use crate::display::TextDisplay;
+use crate::display::render::render;
+use crate::logic::{ButtonAction, GameEvent, GameState, InStartState, RuleSet};
+
+/// Runs the game without any hardware, advancing a virtual clock in fixed steps.
+///
+/// With the same rules, seed, step and inputs every run produces the same frames,
+/// which makes full games usable as golden-frame regression tests on the host.
+pub struct Simulator {
+    /// Only `None` while the state is being updated
+    state: Option<GameState>,
+    display: TextDisplay,
+    started: Instant,
+    now: Instant,
+    step: Duration,
+    steps: u64,
+}
+
+/// Result of a single simulation step
+#[derive(Clone)]
+pub struct Frame {
+    /// Number of steps simulated so far, including this one
+    pub step: u64,
+    pub display: TextDisplay,
+    /// Score of the running or finished game, `None` in the start menu
+    pub score: Option<u32>,
+    /// Events raised during this step
+    pub events: Vec<GameEvent>,
+}
+
+impl Simulator {
+    /// Starts in the start menu. The seed determines the sequence of random pieces.
+    pub fn new(rules: RuleSet, seed: u64, step: Duration) -> Self {
+        let now = Instant::now();
+        let mut simulator = Self {
+            state: Some(GameState::StartMenu(InStartState::with_seed(
+                rules, now, seed,
+            ))),
+            display: TextDisplay::new(),
+            started: now,
+            now,
+            step,
+            steps: 0,
+        };
+        simulator.render();
+        simulator
+    }
+
+    /// Advances the virtual clock by one step, feeding in the given input
+    pub fn step(&mut self, button_action: Option<ButtonAction>) -> Frame {
+        self.now += self.step;
+        self.steps += 1;
+
+        let mut events = Vec::new();
+        let state = self.state.take().expect("state is always present");
+        self.state = Some(state.update(button_action, self.now, &mut events));
+        self.render();
+
+        Frame {
+            step: self.steps,
+            display: self.display.clone(),
+            score: self.state().score(),
+            events,
+        }
+    }
+
+    /// Advances one step per input, returning the frame after every step
+    pub fn run(
+        &mut self,
+        button_actions: impl IntoIterator<Item = Option<ButtonAction>>,
+    ) -> Vec<Frame> {
+        button_actions
+            .into_iter()
+            .map(|button_action| self.step(button_action))
+            .collect()
+    }
+
+    /// Advances without input for the given virtual time, rounded up to whole steps
+    pub fn wait(&mut self, duration: Duration) -> Vec<Frame> {
+        let steps = duration.as_nanos().div_ceil(self.step.as_nanos().max(1));
+        self.run((0..steps).map(|_| None))
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.state.as_ref().expect("state is always present")
+    }
+
+    /// The most recently rendered frame
+    pub fn display(&self) -> &TextDisplay {
+        &self.display
+    }
+
+    /// Virtual time passed since the simulation started
+    pub fn elapsed(&self) -> Duration {
+        self.now.duration_since(self.started)
+    }
+
+    fn render(&mut self) {
+        let state = self.state.as_ref().expect("state is always present");
+        render(state, &mut self.display);
+    }
+}
+
+/// Inputs that rotate each piece the given number of times, move it by the given
+/// number of columns and drop it, then wait `settle_steps` steps for it to lock
+pub fn placement_script(
+    placements: impl IntoIterator<Item = (usize, i16)>,
+    settle_steps: usize,
+) -> Vec<Option<ButtonAction>> {
+    let mut script = Vec::new();
+    for (rotations, dx) in placements {
+        script.extend(std::iter::repeat_n(Some(ButtonAction::Rotate), rotations));
+        let direction = if dx < 0 {
+            ButtonAction::MoveLeft
+        } else {
+            ButtonAction::MoveRight
+        };
+        script.extend(std::iter::repeat_n(
+            Some(direction),
+            dx.unsigned_abs() as usize,
+        ));
+        script.push(Some(ButtonAction::MoveDown));
+        script.extend(std::iter::repeat_n(None, settle_steps));
+    }
+    script
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use game::display::TextDisplay;
+use game::logic::{ButtonAction, GameEvent, GameMode, GameStats, RuleSet};
+use game::simulator::{Frame, Simulator, placement_script};
+
+/// How often each piece is rotated and how far it is moved before it is dropped,
+/// chosen so that the first line is cleared with the sixth piece
+const PLACEMENTS: [(usize, i16); 6] = [(1, -4), (3, -1), (1, 0), (0, -1), (0, -3), (0, 2)];
+
+/// Pieces dropped straight down afterwards, until the stack tops out
+const DROPS: usize = 9;
+
+/// Steps between two pieces, enough for the dropped piece to lock
+const SETTLE_STEPS: usize = 20;
+
+fn script() -> Vec<Option<ButtonAction>> {
+    // Play is the first item of the start menu
+    let mut script = vec![Some(ButtonAction::Rotate)];
+    let placements = PLACEMENTS.into_iter().chain([(0, 0); DROPS]);
+    script.extend(placement_script(placements, SETTLE_STEPS));
+    script
+}
+
+fn play() -> Vec<Frame> {
+    let mut simulator = Simulator::new(RuleSet::default(), 42, Duration::from_millis(50));
+    simulator.run(script())
+}
+
+/// The frame with `#` for lit and `.` for dark pixels, one line per row
+fn pixels(display: &TextDisplay) -> String {
+    display
+        .data
+        .iter()
+        .map(|row| format!("{row:08b}\n").replace('1', "#").replace('0', "."))
+        .collect()
+}
+
+fn frame(frames: &[Frame], step: u64) -> &Frame {
+    &frames[step as usize - 1]
+}
+
+#[test]
+fn same_inputs_render_the_same_frames() {
+    let first: Vec<_> = play().iter().map(|frame| frame.display.clone()).collect();
+    let second: Vec<_> = play().iter().map(|frame| frame.display.clone()).collect();
+    assert!(first == second);
+}
+
+#[test]
+fn clears_a_line() {
+    let frames = play();
+
+    let before = frame(&frames, 130);
+    assert_eq!(before.score, Some(0));
+
+    let cleared = frame(&frames, 131);
+    assert_eq!(cleared.score, Some(10));
+    assert!(cleared.events.contains(&GameEvent::LinesCleared {
+        count: 1,
+        rows: vec![30],
+    }));
+    // The full row is still shown while it is wiped
+    assert_eq!(
+        pixels(&cleared.display),
+        "\
+........
+........
+....#...
+....#...
+...##...
+........
+........
+########
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+........
+#..##...
+#######.
+########
+.####.##
+"
+    );
+
+    // Once the animation is over the rows above collapse
+    let collapsed = frame(&frames, 140);
+    assert!(pixels(&collapsed.display).ends_with("#..##...\n#######.\n.####.##\n"));
+}
+
+#[test]
+fn tops_out() {
+    let frames = play();
+
+    let over = frame(&frames, 317);
+    assert_eq!(over.score, Some(10));
+    assert!(over.events.contains(&GameEvent::GameOver {
+        stats: GameStats {
+            score: 10,
+            lines: 1,
+            level: 1,
+            pieces: 15,
+            mode: GameMode::Marathon,
+        },
+    }));
+    assert_eq!(
+        pixels(&over.display),
+        "\
+........
+........
+........
+........
+...#....
+...#....
+...##...
+....#...
+...##...
+...#....
+...##...
+...##...
+....#...
+....#...
+...##...
+...#....
+...##...
+....#...
+....#...
+...##...
+...##...
+...##...
+...#....
+...#....
+...#....
+...##...
+....#...
+....#...
+...##...
+#..##...
+#######.
+.####.##
+"
+    );
+
+    // Nothing but the game over happens after the stack topped out
+    let events = frames.iter().flat_map(|frame| &frame.events);
+    let game_overs = events
+        .filter(|event| matches!(event, GameEvent::GameOver { .. }))
+        .count();
+    assert_eq!(game_overs, 1);
+}